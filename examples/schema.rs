@@ -0,0 +1,32 @@
+use std::env::current_dir;
+use std::fs::{read_to_string, write};
+
+use cosmwasm_schema::{export_schema, schema_for, write_api};
+use serde_json::Value;
+
+use incentive::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, ReceiveMsg};
+
+fn main() {
+    write_api! {
+        instantiate: InstantiateMsg,
+        execute: ExecuteMsg,
+        query: QueryMsg,
+        migrate: MigrateMsg,
+    }
+
+    // the cw20 hook message is sent through the stake token, not to the contract directly,
+    // so it is added to the full API by hand
+    let mut out_dir = current_dir().unwrap();
+    out_dir.push("schema");
+    let path = out_dir.join("incentive.json");
+    let mut api: Value = serde_json::from_str(&read_to_string(&path).unwrap()).unwrap();
+    api.as_object_mut().unwrap().insert(
+        "receive".to_string(),
+        serde_json::to_value(schema_for!(ReceiveMsg)).unwrap(),
+    );
+    write(&path, serde_json::to_string_pretty(&api).unwrap() + "\n").unwrap();
+    println!("Exported the receive message to {}", path.to_str().unwrap());
+
+    out_dir.push("raw");
+    export_schema(&schema_for!(ReceiveMsg), &out_dir);
+}
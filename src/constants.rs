@@ -3,7 +3,11 @@ pub const TWO_YEAR_SECONDS: u64 = 63072000;
 pub const ONE_YEAR_SECONDS: u64 = 31536000;
 pub const SIX_MONTH_SECONDS: u64 = 15768000;
 pub const ONE_MONTH_SECONDS: u64 = 2628000;
+pub const UNBONDING_SECONDS: u64 = 2419200;
+// a sixth of the unbonding period, so a validator has at most 6 unbonding entries of the contract
+pub const UNDELEGATION_EPOCH_SECONDS: u64 = 403200;
 
+pub const TWO_YEAR_APY: u64 = 100;
 pub const ONE_YEAR_APY: u64 = 40;
 pub const SIX_MONTH_APY: u64 = 20;
 pub const ONE_MONTH_APY: u64 = 10;
@@ -13,4 +17,19 @@ pub const LOCKED_ONE_YEAR: u64 = 2;
 pub const LOCKED_SIX_MONTH: u64 = 1;
 pub const LOCKED_ONE_MONTH: u64 = 0;
 
+pub const MAX_LOCK_SECONDS: u64 = 157680000;
+
+// one token of 6 decimals, so dust records are not worth sending around
+pub const DEFAULT_MIN_STAKE_AMOUNT: u128 = 1_000_000;
+pub const DEFAULT_MAX_POSITIONS_PER_ADDRESS: u64 = 50;
+
+pub const MAX_ADMIN_TIMELOCK_SECONDS: u64 = 2592000;
+pub const MAX_REWARD_VESTING_SECONDS: u64 = ONE_YEAR_SECONDS;
+
+pub const DEFAULT_MIN_REWARD_INTERVAL: u64 = 86400;
+pub const DEFAULT_MAX_REWARD_INTERVAL: u64 = TWO_YEAR_SECONDS;
+
+pub const POSITION_NFT_NAME: &str = "HOLE Staking Position";
+pub const POSITION_NFT_SYMBOL: &str = "HOLEPOS";
+
 
@@ -1,31 +1,51 @@
-use crate::constants::{self, LOCKED_TWO_YEAR};
+use crate::constants;
 use crate::error::ContractError;
 use crate::msg::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, ReceiveMsg,
-    RewardDistribution, StakerInfo, StakerListResponse, TestBalanceResponse,
-    Token1ForToken2PriceResponse, Token2ForToken1PriceResponse,
+    AdminAction, AirdropStageResponse, BalanceResponse, CapacityResponse, ConfigResponse,
+    ConstantsUpdate, DelegationResponse, ExecuteMsg, InstantiateMsg, IsClaimedResponse,
+    LatestStageResponse, LpPool, MigrateMsg, PendingAdminAction, PendingAdminActionsResponse,
+    PendingRewardResponse, Pool, PoolResponse, PoolsResponse, Position, PositionRewardResponse,
+    QueryMsg, ReceiveMsg, RewardDistribution, RewardVesting, RewardVestingEntryResponse,
+    RewardVestingResponse, StakerListResponse, StakerResponse, Tier, TierCapacityResponse,
+    Unbonding, UnbondingEntryResponse, UnbondingsResponse, UncheckedDenom, UncheckedLpPool,
+    WasmswapInfoResponse,
+};
+use crate::state::{
+    positions, AirdropStage, Config, DelegationState, UndelegationBatch, ADMIN_ACTIONS,
+    ADMIN_ACTION_COUNT, AIRDROP_CLAIMED, AIRDROP_STAGES, CONFIG, DELEGATION, LATEST_STAGE,
+    LEGACY_CONFIG, LEGACY_STAKERS, NUM_TOKENS, OPERATORS, POOLS, POOL_COUNT, POOL_STAKED,
+    POSITION_APPROVALS, POSITION_COUNT, REWARD_VESTING, REWARD_VESTING_TOTAL, TIER_STAKED,
+    UNBONDINGS, UNDELEGATION_BATCHES,
 };
-use crate::state::{Config, CONFIG, RANKS, RANK_STAKERS, STAKERS};
 use crate::util;
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    attr, from_binary, to_binary, Addr, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Order,
-    QueryRequest, Response, StdResult, Storage, Uint128, WasmMsg, WasmQuery,
+    attr, from_json, to_json_binary, Addr, Api, Binary, Coin, CosmosMsg, Decimal, Deps, DepsMut,
+    DistributionMsg, Env, Event, MessageInfo, Order, QuerierWrapper, Response, StakingMsg,
+    StdError, StdResult, Storage, Uint128,
 };
 use cw2::{get_contract_version, set_contract_version};
-use cw20::{
-    BalanceResponse as CW20BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg, Denom,
-    TokenInfoResponse,
+use cw20::{Cw20QueryMsg, Cw20ReceiveMsg, Denom};
+use cw721::{
+    AllNftInfoResponse, Approval, ApprovalResponse, ApprovalsResponse, ContractInfoResponse,
+    Cw721ReceiveMsg, NftInfoResponse, NumTokensResponse, OperatorsResponse, OwnerOfResponse,
+    TokensResponse,
 };
 use cw_storage_plus::Bound;
-use cw_utils::maybe_addr;
+use cw_utils::{one_coin, Expiration, Scheduled};
+use semver::Version;
+use sha2::Digest;
 
 // Version info, for migration info
 const CONTRACT_NAME: &str = "incentive";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 const MULTIPLE: u128 = 100u128;
+
+// pagination of the position nft queries
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
 ///////////////////////////////////////////////////////// this func is called for instantiating the contract //////////////////////////////////
 ///
 ///         input params: owner address
@@ -36,7 +56,7 @@ const MULTIPLE: u128 = 100u128;
 ///                       burn wallet address for reward
 ///                       artists wallet address for reward
 ///
-///         
+///         The default pool(id 0) stakes the stake token for the stake token and reward token denom.
 /// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -44,23 +64,125 @@ pub fn instantiate(
     _env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
+    let min_reward_interval = msg
+        .min_reward_interval
+        .unwrap_or(constants::DEFAULT_MIN_REWARD_INTERVAL);
+    let max_reward_interval = msg
+        .max_reward_interval
+        .unwrap_or(constants::DEFAULT_MAX_REWARD_INTERVAL);
+    if min_reward_interval == 0 || min_reward_interval > max_reward_interval {
+        return Err(ContractError::InvalidInput {});
+    }
+
+    // a dao can be set as owner at deployment
+    let owner = match msg.owner {
+        Some(owner) => deps.api.addr_validate(&owner)?,
+        None => info.sender.clone(),
+    };
+
     let config = Config {
-        owner: info.sender.clone(),
-        stake_token_address: msg.stake_token_address,
+        owner,
+        stake_token_address: deps.api.addr_validate(&msg.stake_token_address)?,
         reward_token_denom: msg.reward_token_denom,
         reward_interval: msg.reward_interval,
-        reward_charity_address: msg.reward_charity_address,
-        reward_burn_address: msg.reward_burn_address,
-        reward_artists_address: msg.reward_artists_address,
+        min_reward_interval,
+        max_reward_interval,
+        reward_charity_address: deps.api.addr_validate(&msg.reward_charity_address)?,
+        reward_burn_address: deps.api.addr_validate(&msg.reward_burn_address)?,
+        reward_artists_address: deps.api.addr_validate(&msg.reward_artists_address)?,
         enabled: true,
+        validators: vec![],
+        min_stake_amount: Uint128::from(constants::DEFAULT_MIN_STAKE_AMOUNT),
+        max_positions_per_address: constants::DEFAULT_MAX_POSITIONS_PER_ADDRESS,
+        stake_on_behalf_allowlist: None,
+        reward_vesting_seconds: 0,
+        stake_paused: false,
+        claim_paused: false,
+        unstake_paused: false,
+        emergency: false,
+        admin_timelock_seconds: 0,
     };
+    check_reward_interval(&config, config.reward_interval)?;
     CONFIG.save(deps.storage, &config)?;
+    init_state(deps.storage, &config)?;
 
     Ok(Response::default())
 }
+///////////////////////////////////////////////////////// this func is called for saving the initial state except the config //////////////////////////////////
+///
+///         This is called on instantiation and on the migration from the contract before 2.0.0.
+///         input params: config of the contract
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+fn init_state(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
+    DELEGATION.save(
+        storage,
+        &DelegationState {
+            total_delegated: Uint128::zero(),
+            reward_index: Decimal::zero(),
+            pending_undelegation: Uint128::zero(),
+            batch_id: 0,
+            last_undelegation_time: 0,
+            unbonded: Uint128::zero(),
+            next_release_batch: 0,
+            unpaid_reward: Uint128::zero(),
+        },
+    )?;
+
+    // the default pool stakes hole for the hole and juno reward
+    let pool = Pool {
+        stake_asset: Denom::Cw20(config.stake_token_address.clone()),
+        reward_asset: Denom::Cw20(config.stake_token_address.clone()),
+        native_reward_asset: Denom::Native(config.reward_token_denom.clone()),
+        tiers: default_tiers(),
+        enabled: true,
+        lp: None,
+        delegated: false,
+        max_total_staked: None,
+    };
+    POOLS.save(storage, 0u64, &pool)?;
+    POOL_COUNT.save(storage, &1u64)?;
+
+    POSITION_COUNT.save(storage, &0u64)?;
+    NUM_TOKENS.save(storage, &0u64)?;
+    Ok(())
+}
+///////////////////////////////////////////////////////// this func is called for getting the default lock tiers //////////////////////////////////
+///
+///         The index of the tier is the lock_type of the stake message.
+///         0: 10% for over 30 days
+///         1: 20% for over 6 months
+///         2: 40% for over 1 years
+///         3: 100% for over 2 years
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn default_tiers() -> Vec<Tier> {
+    vec![
+        Tier {
+            lock_seconds: constants::ONE_MONTH_SECONDS,
+            apy: constants::ONE_MONTH_APY,
+            capacity: None,
+        },
+        Tier {
+            lock_seconds: constants::SIX_MONTH_SECONDS,
+            apy: constants::SIX_MONTH_APY,
+            capacity: None,
+        },
+        Tier {
+            lock_seconds: constants::ONE_YEAR_SECONDS,
+            apy: constants::ONE_YEAR_APY,
+            capacity: None,
+        },
+        Tier {
+            lock_seconds: constants::TWO_YEAR_SECONDS,
+            apy: constants::TWO_YEAR_APY,
+            capacity: None,
+        },
+    ]
+}
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
@@ -70,23 +192,161 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::UpdateOwner { owner } => execute_update_owner(deps, info, owner),
+        ExecuteMsg::UpdateOwner { owner } => {
+            check_no_timelock(&deps)?;
+            execute_update_owner(deps, info, owner)
+        }
         ExecuteMsg::UpdateEnabled { enabled } => execute_update_enabled(deps, info, enabled),
-        ExecuteMsg::UpdateConstants { reward_interval } => {
-            execute_update_constants(deps, info, reward_interval)
+        ExecuteMsg::UpdatePaused {
+            stake_paused,
+            claim_paused,
+            unstake_paused,
+        } => execute_update_paused(deps, info, stake_paused, claim_paused, unstake_paused),
+        ExecuteMsg::UpdateEmergency { emergency } => {
+            execute_update_emergency(deps, info, emergency)
+        }
+        ExecuteMsg::UpdateConstants(update) => {
+            check_no_timelock(&deps)?;
+            execute_update_constants(deps, info, update)
         }
+        ExecuteMsg::UpdateStakeLimits {
+            min_stake_amount,
+            max_positions_per_address,
+        } => execute_update_stake_limits(deps, info, min_stake_amount, max_positions_per_address),
+        ExecuteMsg::UpdateStakeOnBehalfAllowlist { allowlist } => {
+            execute_update_stake_on_behalf_allowlist(deps, info, allowlist)
+        }
+        ExecuteMsg::UpdateRewardVesting { vesting_seconds } => {
+            check_no_timelock(&deps)?;
+            execute_update_reward_vesting(deps, info, vesting_seconds)
+        }
+        ExecuteMsg::QueueAdminAction { action } => {
+            execute_queue_admin_action(deps, env, info, action)
+        }
+        ExecuteMsg::ExecuteAdminAction { id } => execute_admin_action(deps, env, info, id),
+        ExecuteMsg::CancelAdminAction { id } => execute_cancel_admin_action(deps, info, id),
+        ExecuteMsg::CreatePool {
+            stake_asset,
+            reward_asset,
+            native_reward_asset,
+            tiers,
+            lp,
+            delegated,
+            max_total_staked,
+        } => {
+            let pool = Pool {
+                stake_asset: validate_denom(deps.api, stake_asset)?,
+                reward_asset: validate_denom(deps.api, reward_asset)?,
+                native_reward_asset: validate_denom(deps.api, native_reward_asset)?,
+                tiers,
+                enabled: true,
+                lp: lp.map(|lp| validate_lp_pool(deps.api, lp)).transpose()?,
+                delegated,
+                max_total_staked,
+            };
+            execute_create_pool(deps, info, pool)
+        }
+        ExecuteMsg::UpdatePool {
+            pool_id,
+            tiers,
+            enabled,
+        } => {
+            // the tiers set the reward, so they are changed through the timelock like the constants
+            if tiers.is_some() {
+                check_no_timelock(&deps)?;
+            }
+            execute_update_pool(deps, info, pool_id, tiers, enabled)
+        }
+        ExecuteMsg::UpdatePoolCap {
+            pool_id,
+            max_total_staked,
+        } => execute_update_pool_cap(deps, info, pool_id, max_total_staked),
         ExecuteMsg::Receive(msg) => execute_receive(deps, env, info, msg),
-        ExecuteMsg::WithdrawReward { amount } => execute_withdraw_reward(deps, env, info, amount),
-        ExecuteMsg::WithdrawStake { amount } => execute_withdraw_stake(deps, env, info, amount),
-        ExecuteMsg::ClaimReward { distribution } => {
-            execute_claim_reward(deps, env, info, distribution)
+        ExecuteMsg::Stake {
+            pool_id,
+            lock_type,
+            recipient,
+        } => execute_stake_native(deps, env, info, pool_id, lock_type, recipient),
+        ExecuteMsg::WithdrawReward { amount } => {
+            check_no_timelock(&deps)?;
+            execute_withdraw_reward(deps, env, info, amount)
+        }
+        ExecuteMsg::WithdrawStake { amount } => {
+            check_no_timelock(&deps)?;
+            execute_withdraw_stake(deps, env, info, amount)
+        }
+        ExecuteMsg::ClaimReward {
+            pool_id,
+            distribution,
+        } => execute_claim_reward(deps, env, info, pool_id, distribution),
+        ExecuteMsg::Unstake { pool_id } => execute_unstake(deps, env, info, pool_id),
+        ExecuteMsg::EmergencyWithdraw {} => execute_emergency_withdraw(deps, env, info),
+        ExecuteMsg::TransferPosition {
+            position_id,
+            recipient,
+        } => execute_transfer_position(deps, env, info, position_id, recipient),
+        ExecuteMsg::ExtendLock {
+            position_id,
+            new_lock_type,
+        } => execute_extend_lock(deps, env, info, position_id, new_lock_type),
+        ExecuteMsg::MergePositions { position_ids } => {
+            execute_merge_positions(deps, env, info, position_ids)
         }
-        ExecuteMsg::Unstake {} => execute_unstake(deps, env, info),
+        ExecuteMsg::SplitPosition {
+            position_id,
+            amounts,
+        } => execute_split_position(deps, info, position_id, amounts),
+        ExecuteMsg::RegisterMerkleRoot {
+            merkle_root,
+            start,
+            expiration,
+            pool_id,
+        } => execute_register_merkle_root(deps, info, merkle_root, start, expiration, pool_id),
+        ExecuteMsg::ClaimAirdrop {
+            stage,
+            amount,
+            proof,
+            lock_type,
+        } => execute_claim_airdrop(deps, env, info, stage, amount, proof, lock_type),
+        ExecuteMsg::WithdrawAirdropRemainder { stage } => {
+            execute_withdraw_airdrop_remainder(deps, env, info, stage)
+        }
+        ExecuteMsg::UpdateValidators { validators } => {
+            check_no_timelock(&deps)?;
+            execute_update_validators(deps, info, validators)
+        }
+        ExecuteMsg::HarvestDelegationRewards {} => execute_harvest_delegation_rewards(deps, env),
+        ExecuteMsg::WithdrawUnbonded {} => execute_withdraw_unbonded(deps, env, info),
+        ExecuteMsg::WithdrawVested {} => execute_withdraw_vested(deps, env, info),
+        ExecuteMsg::TransferNft {
+            recipient,
+            token_id,
+        } => execute_transfer_nft(deps, env, info, recipient, token_id),
+        ExecuteMsg::SendNft {
+            contract,
+            token_id,
+            msg,
+        } => execute_send_nft(deps, env, info, contract, token_id, msg),
+        ExecuteMsg::Approve {
+            spender,
+            token_id,
+            expires,
+        } => execute_approve(deps, env, info, spender, token_id, expires),
+        ExecuteMsg::Revoke { spender, token_id } => {
+            execute_revoke(deps, env, info, spender, token_id)
+        }
+        ExecuteMsg::ApproveAll { operator, expires } => {
+            execute_approve_all(deps, env, info, operator, expires)
+        }
+        ExecuteMsg::RevokeAll { operator } => execute_revoke_all(deps, info, operator),
     }
 }
 ///////////////////////////////////////////////////////// this func is called when user click stake button on the frontend //////////////////////////////////
 ///
+///         The cw20 token sent must be the stake asset of the pool.
+///         The owner deposits the airdrop of the cw20 pools with this function as well.
 ///         input params: customer's wallet address
+///                       pool id
 ///                       lock_type for claim reward
 ///         
 ///     
@@ -98,377 +358,2757 @@ pub fn execute_receive(
     wrapper: Cw20ReceiveMsg,
 ) -> Result<Response, ContractError> {
     check_enabled(&deps, &info)?;
-    let mut cfg = CONFIG.load(deps.storage)?;
-
-    if wrapper.amount == Uint128::zero() {
-        return Err(ContractError::InvalidInput {});
-    }
-    let user_addr = &deps.api.addr_validate(&wrapper.sender)?;
 
-    if info.sender.clone() != cfg.stake_token_address {
-        return Err(ContractError::UnacceptableToken {});
-    }
+    let user_addr = deps.api.addr_validate(&wrapper.sender)?;
 
-    let msg: ReceiveMsg = from_binary(&wrapper.msg)?;
+    let msg: ReceiveMsg = from_json(&wrapper.msg)?;
     match msg {
-        ReceiveMsg::Stake { lock_type } => {
-            let mut list = STAKERS
-                .load(deps.storage, user_addr.clone())
-                .unwrap_or(vec![]);
-
-            list.push(StakerInfo {
-                address: user_addr.clone(),
-                amount: wrapper.amount,
-                reward: Uint128::zero(),
-                last_time: env.block.time.seconds(),
-                lock_type: match lock_type {
-                    0 => constants::ONE_MONTH_SECONDS,
-                    1 => constants::SIX_MONTH_SECONDS,
-                    2 => constants::ONE_YEAR_SECONDS,
-                    _ => constants::TWO_YEAR_SECONDS,
-                },
-            });
-
-            STAKERS.save(deps.storage, user_addr.clone(), &list)?;
-
-            return Ok(Response::new().add_attributes(vec![
-                attr("action", "stake"),
-                attr("address", user_addr.clone()),
-                attr("amount", wrapper.amount),
-            ]));
+        ReceiveMsg::Stake {
+            pool_id,
+            lock_type,
+            recipient,
+        } => {
+            let owner = stake_recipient(&deps, &user_addr, recipient)?;
+            stake(
+                deps,
+                env,
+                user_addr,
+                owner,
+                pool_id,
+                Denom::Cw20(info.sender.clone()),
+                wrapper.amount,
+                lock_type,
+            )
+        }
+        ReceiveMsg::RegisterMerkleRoot {
+            merkle_root,
+            start,
+            expiration,
+            pool_id,
+        } => {
+            // authorize owner
+            if user_addr != CONFIG.load(deps.storage)?.owner {
+                return Err(ContractError::Unauthorized {});
+            }
+            register_merkle_root(
+                deps,
+                merkle_root,
+                start,
+                expiration,
+                pool_id,
+                Denom::Cw20(info.sender.clone()),
+                wrapper.amount,
+            )
         }
     }
 }
-///////////////////////////////////////////////////////// this func is called for calculating the reward amount  //////////////////////////////////
+///////////////////////////////////////////////////////// this func is called when user stakes native token on the frontend //////////////////////////////////
 ///
+///         The native token sent must be the stake asset of the pool.
+///         input params: pool id
+///                       lock_type for claim reward
+///                       recipient of the staking record(optional, the sender by default)
 ///         
 ///     
 /// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
-pub fn update_reward(
-    storage: &mut dyn Storage,
+pub fn execute_stake_native(
+    deps: DepsMut,
     env: Env,
-    address: Addr,
-) -> Result<Uint128, ContractError> {
-    let mut exists = STAKERS.load(storage, address.clone()).unwrap_or(vec![]);
-    let cfg = CONFIG.load(storage)?;
-    let mut total_reward = Uint128::zero();
-
-    for i in 0..exists.len() {
-        let staked_time = env.block.time.seconds() - exists[i].last_time;
-        let mut reward_tot = Uint128::zero();
+    info: MessageInfo,
+    pool_id: u64,
+    lock_type: u64,
+    recipient: Option<String>,
+) -> Result<Response, ContractError> {
+    check_enabled(&deps, &info)?;
 
-        if staked_time < exists[i].lock_type {
-            reward_tot = Uint128::zero();
-        } else {
-            match exists[i].lock_type {
-                constants::TWO_YEAR_SECONDS => {
-                    if staked_time >= constants::TWO_YEAR_SECONDS {
-                        // 100% for over 2 years
-                        reward_tot = Uint128::from(exists[i].amount);
-                    }
-                }
-                constants::ONE_YEAR_SECONDS => {
-                    if staked_time >= constants::ONE_YEAR_SECONDS {
-                        // 40% for over 1 years
-                        reward_tot = exists[i].amount * Uint128::from(constants::ONE_YEAR_APY)
-                            / Uint128::from(MULTIPLE);
-                    }
-                }
-                constants::SIX_MONTH_SECONDS => {
-                    if staked_time >= constants::SIX_MONTH_SECONDS {
-                        // 20% for over 6 months
-                        reward_tot = exists[i].amount * Uint128::from(constants::SIX_MONTH_APY)
-                            / Uint128::from(MULTIPLE);
-                    }
-                }
-                _ => {
-                    if staked_time >= constants::ONE_MONTH_SECONDS {
-                        // 10% for over 30 days
-                        reward_tot = exists[i].amount * Uint128::from(constants::ONE_MONTH_APY)
-                            / Uint128::from(MULTIPLE);
-                    }
-                }
-            }
-        };
-        let reward = reward_tot * (Uint128::from(cfg.reward_interval))
-            / (Uint128::from(constants::ONE_YEAR_SECONDS));
-        exists[i].reward = reward; //* Uint128::from(staked_time) / Uint128::from(cfg.reward_interval); //for test
-        total_reward += exists[i].reward;
+    let coin = one_coin(&info)?;
+    let owner = stake_recipient(&deps, &info.sender, recipient)?;
+    stake(
+        deps,
+        env,
+        info.sender.clone(),
+        owner,
+        pool_id,
+        Denom::Native(coin.denom),
+        coin.amount,
+        lock_type,
+    )
+}
+///////////////////////////////////////////////////////// this func is called for getting the owner of a new staking record //////////////////////////////////
+///
+///         Contracts like the vesting and airdrop contracts can stake for the users.
+///         Only the contracts in the allowlist can stake for another address, nobody can if it is not set.
+///         input params: sender address
+///                       recipient address(optional)
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+fn stake_recipient(
+    deps: &DepsMut,
+    sender: &Addr,
+    recipient: Option<String>,
+) -> Result<Addr, ContractError> {
+    let recipient = match recipient {
+        Some(recipient) => deps.api.addr_validate(&recipient)?,
+        None => return Ok(sender.clone()),
+    };
+    if recipient == *sender {
+        return Ok(recipient);
     }
 
-    STAKERS.save(storage, address.clone(), &exists).unwrap();
-
-    return Ok(total_reward);
+    // the staking records of others fill the received cap of the recipient, so it is closed by default
+    let cfg = CONFIG.load(deps.storage)?;
+    match cfg.stake_on_behalf_allowlist {
+        Some(allowlist) if allowlist.contains(sender) => Ok(recipient),
+        _ => Err(ContractError::Unauthorized {}),
+    }
 }
-///////////////////////////////////////////////////////// this func is called when we click reward button on frontend//////////////////////////////////
-///
-///         input params: customer's wallet address
-///                       juno reward flag(This is true when rank is bigger than 500, if this is true, customer can get juno reward, if false, cutomer can get only hole reward)
-///                       artists wallet percent
-///                       burn wallet percent
-///                       charity wallet percent
-///                       my wallet percent = 100 - artists_percent - burn_percent - charity_percent
+///////////////////////////////////////////////////////// this func is called for creating new staking record //////////////////////////////////
 ///
+///         input params: customer's wallet address(the stake over the capacity is refunded to it)
+///                       owner address of the staking record
+///                       pool id
+///                       staked asset and amount
+///                       lock_type for claim reward (index of the tier of the pool)
+///         
 ///     
-/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
-pub fn execute_claim_reward(
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+#[allow(clippy::too_many_arguments)]
+fn stake(
     deps: DepsMut,
     env: Env,
-    info: MessageInfo,
-    reward_distribution: RewardDistribution,
+    sender: Addr,
+    user_addr: Addr,
+    pool_id: u64,
+    asset: Denom,
+    amount: Uint128,
+    lock_type: u64,
 ) -> Result<Response, ContractError> {
-    check_enabled(&deps, &info)?;
     let cfg = CONFIG.load(deps.storage)?;
+    if cfg.stake_paused {
+        return Err(ContractError::StakePaused {});
+    }
+    if amount.is_zero() || amount < cfg.min_stake_amount {
+        return Err(ContractError::InvalidInput {});
+    }
+    let received = sender != user_addr;
+    check_position_limit(deps.storage, &cfg, pool_id, &user_addr, received, 1)?;
 
-    let cw20_reward = update_reward(deps.storage, env.clone(), info.sender.clone()).unwrap();
-
-    let mut list = STAKERS
-        .load(deps.storage, info.sender.clone())
-        .unwrap_or(vec![]);
-
-    // // neet to change reward to ujuno
-    // // test for 1/1000 juno
-    // // let juno_reward = querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
-    // //     contract_addr: pool_address.clone().into(),
-    // //     msg: to_binary(&WasmswapQueryMsg::Token2ForToken1Price {
-    // //         token2_amount: amount
-    // //     })?,
-    // // }))?;
-    let juno_reward = cw20_reward; //for test
-    
-    let tot_reward_token = util::get_token_amount(
-        deps.querier,
-        Denom::Native(cfg.reward_token_denom.clone()),
-        env.contract.address.clone(),
-    )?;
-
-    if tot_reward_token < juno_reward {
-        return Err(ContractError::NotEnoughReward {});
+    let pool = load_enabled_pool(deps.storage, pool_id)?;
+    if pool.stake_asset != asset {
+        return Err(ContractError::UnacceptableToken {});
     }
 
-    let mut msgs: Vec<CosmosMsg> = vec![];
+    let tier = select_tier(&pool, lock_type)?;
+    let lock_seconds = tier.lock_seconds;
 
-    for i in 0..list.len() {
-        list[i].last_time = env.block.time.seconds();
+    // only the amount within the capacity of the pool and the tier is staked, the rest is refunded
+    let remaining = remaining_capacity(deps.storage, pool_id, &pool, tier)?;
+    let staked = amount.min(remaining.unwrap_or(amount));
+    if staked.is_zero() || staked < cfg.min_stake_amount {
+        return Err(ContractError::CapacityExceeded {});
     }
-    STAKERS.save(deps.storage, info.sender.clone(), &list)?;
+    let refund = amount - staked;
+    let amount = staked;
 
-    if !cw20_reward.is_zero() {
+    let (position_id, mut msgs) = open_position(
+        deps,
+        &env,
+        pool_id,
+        &pool,
+        user_addr.clone(),
+        amount,
+        lock_seconds,
+        received,
+    )?;
+    if !refund.is_zero() {
         msgs.push(util::transfer_token_message(
-            Denom::Cw20(cfg.stake_token_address.clone()),
-            cw20_reward,
-            info.sender.clone(),
+            asset.clone(),
+            refund,
+            sender.clone(),
         )?);
     }
 
-    if reward_distribution.juno_reward {
-        if reward_distribution.charity != 0u64 {
-            let reward_charity =
-                juno_reward * Uint128::from(reward_distribution.charity) / Uint128::from(100u64);
-            if !reward_charity.is_zero() {
-                msgs.push(util::transfer_token_message(
-                    Denom::Native(cfg.reward_token_denom.clone()),
-                    reward_charity,
-                    cfg.reward_charity_address.clone(),
-                )?);
-            }
-        }
-
-        if reward_distribution.burn != 0u64 {
-            let reward_burn =
-                juno_reward * Uint128::from(reward_distribution.burn) / Uint128::from(100u64);
-            if !reward_burn.is_zero() {
-                msgs.push(util::transfer_token_message(
-                    Denom::Native(cfg.reward_token_denom.clone()),
-                    reward_burn,
-                    cfg.reward_burn_address.clone(),
-                )?);
-            }
-        }
+    Ok(Response::new().add_messages(msgs).add_attributes(vec![
+        attr("action", "stake"),
+        attr("pool_id", pool_id.to_string()),
+        attr("position_id", position_id.to_string()),
+        attr("sender", sender),
+        attr("address", user_addr),
+        attr("asset", util::denom_key(&asset)),
+        attr("amount", amount),
+        attr("refund_amount", refund),
+    ]))
+}
+///////////////////////////////////////////////////////// this func is called for saving the staked amount as a new staking record //////////////////////////////////
+///
+///         The amount is added to the staked amount of the tier, and the native stake of delegated pools is delegated.
+///         The capacity of the pool and the tier must be checked before.
+///         input params: pool id and pool
+///                       owner address of the staking record
+///                       staked amount
+///                       lock seconds of the tier
+///                       whether the record is staked by another address
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+#[allow(clippy::too_many_arguments)]
+fn open_position(
+    deps: DepsMut,
+    env: &Env,
+    pool_id: u64,
+    pool: &Pool,
+    owner: Addr,
+    amount: Uint128,
+    lock_seconds: u64,
+    received: bool,
+) -> Result<(u64, Vec<CosmosMsg>), ContractError> {
+    add_staked(deps.storage, pool_id, lock_seconds, amount)?;
 
-        if reward_distribution.artists != 0u64 {
-            let reward_artists =
-                juno_reward * Uint128::from(reward_distribution.artists) / Uint128::from(100u64);
-            if !reward_artists.is_zero() {
-                msgs.push(util::transfer_token_message(
-                    Denom::Native(cfg.reward_token_denom.clone()),
-                    reward_artists,
-                    cfg.reward_artists_address.clone(),
-                )?);
-            }
-        }
+    // the reward of lp pools is fixed to the underlying reward asset at the time of staking,
+    // so moving the reserves of the swap before claiming does not change it
+    let swap_info = load_reward_swap_info(deps.querier, pool)?;
+    let base = reward_base(pool, &swap_info, amount);
 
-        if (reward_distribution.charity + reward_distribution.burn + reward_distribution.artists)
-            < 100
-        {
-            let reward_user_rate = 100
-                - (reward_distribution.charity
-                    + reward_distribution.burn
-                    + reward_distribution.artists);
-            let reward_user = juno_reward * Uint128::from(reward_user_rate) / Uint128::from(100u64);
-            if !reward_user.is_zero() {
-                msgs.push(util::transfer_token_message(
-                    Denom::Native(cfg.reward_token_denom.clone()),
-                    reward_user,
-                    info.sender.clone(),
-                )?);
-            }
-        }
+    // the native stake of delegated pools is delegated to the validators
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    let mut reward_index = Decimal::zero();
+    if pool.delegated {
+        (msgs, _) = harvest_delegation_rewards(deps.storage, deps.querier, env)?;
+        msgs.extend(delegate(deps.storage, &pool.stake_asset, amount)?);
+        reward_index = DELEGATION.load(deps.storage)?.reward_index;
     }
 
-    // End
-
-    return Ok(Response::new().add_messages(msgs).add_attributes(vec![
-        attr("action", "claim_reward"),
-        attr("address", info.sender.clone()),
-        attr("reward_amount", Uint128::from(cw20_reward)),
-    ]));
+    let position_id = create_position(
+        deps.storage,
+        Position {
+            position_id: 0,
+            pool_id,
+            owner,
+            asset: pool.stake_asset.clone(),
+            amount,
+            reward_base: base,
+            reward: Uint128::zero(),
+            accrued: Uint128::zero(),
+            last_time: env.block.time.seconds(),
+            lock_type: lock_seconds,
+            reward_index,
+            received,
+        },
+    )?;
+    Ok((position_id, msgs))
 }
-///////////////////////////////////////////////////////// this func is called when we click unstake button on frontend//////////////////////////////////
+///////////////////////////////////////////////////////// this func is called for getting the remaining capacity of a tier //////////////////////////////////
 ///
-///         Using this function, we can unstake all staked token
-///         input params: none
-///         
+///         The remaining capacity is the smaller one of the pool and the tier, None if neither is capped.
 ///     
-/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
-pub fn execute_unstake(
-    deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-) -> Result<Response, ContractError> {
-    check_enabled(&deps, &info)?;
-    let cfg = CONFIG.load(deps.storage)?;
-
-    let mut list = STAKERS.load(deps.storage, info.sender.clone())?;
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+fn remaining_capacity(
+    storage: &dyn Storage,
+    pool_id: u64,
+    pool: &Pool,
+    tier: &Tier,
+) -> StdResult<Option<Uint128>> {
+    let pool_remaining = match pool.max_total_staked {
+        Some(max_total_staked) => {
+            let staked = POOL_STAKED.may_load(storage, pool_id)?.unwrap_or_default();
+            Some(max_total_staked.saturating_sub(staked))
+        }
+        None => None,
+    };
+    let tier_remaining = match tier.capacity {
+        Some(capacity) => {
+            let staked = TIER_STAKED
+                .may_load(storage, (pool_id, tier.lock_seconds))?
+                .unwrap_or_default();
+            Some(capacity.saturating_sub(staked))
+        }
+        None => None,
+    };
 
-    // check if user can unstake this record
-    // env.block.time.seconds(), record.stake_time
-    let mut staked = Uint128::zero();
+    Ok(match (pool_remaining, tier_remaining) {
+        (Some(pool_remaining), Some(tier_remaining)) => Some(pool_remaining.min(tier_remaining)),
+        (pool_remaining, tier_remaining) => pool_remaining.or(tier_remaining),
+    })
+}
 
-    for i in 0..list.len() {
-        staked += list[i].amount;
-    }
+fn add_staked(
+    storage: &mut dyn Storage,
+    pool_id: u64,
+    lock_seconds: u64,
+    amount: Uint128,
+) -> StdResult<()> {
+    POOL_STAKED.update(storage, pool_id, |staked| -> StdResult<_> {
+        Ok(staked.unwrap_or_default().checked_add(amount)?)
+    })?;
+    TIER_STAKED.update(storage, (pool_id, lock_seconds), |staked| -> StdResult<_> {
+        Ok(staked.unwrap_or_default().checked_add(amount)?)
+    })?;
+    Ok(())
+}
 
-    let tot_staked = util::get_token_amount(
-        deps.querier,
-        Denom::Cw20(cfg.stake_token_address.clone()),
-        env.contract.address.clone(),
-    )?;
+fn sub_staked(
+    storage: &mut dyn Storage,
+    pool_id: u64,
+    lock_seconds: u64,
+    amount: Uint128,
+) -> StdResult<()> {
+    POOL_STAKED.update(storage, pool_id, |staked| -> StdResult<_> {
+        Ok(staked.unwrap_or_default().checked_sub(amount)?)
+    })?;
+    TIER_STAKED.update(storage, (pool_id, lock_seconds), |staked| -> StdResult<_> {
+        Ok(staked.unwrap_or_default().checked_sub(amount)?)
+    })?;
+    Ok(())
+}
 
-    if tot_staked < staked {
-        return Err(ContractError::NotEnoughStake {});
-    }
-    for j in 0..list.len() {
-        list.remove(0);
+// moves the amount of a staking record to another tier of the pool, within the capacity of the tier
+fn move_staked_tier(
+    storage: &mut dyn Storage,
+    pool: &Pool,
+    pool_id: u64,
+    from_lock_seconds: u64,
+    to_lock_seconds: u64,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    let capacity = pool
+        .tiers
+        .iter()
+        .find(|tier| tier.lock_seconds == to_lock_seconds)
+        .and_then(|tier| tier.capacity);
+    if let Some(capacity) = capacity {
+        let staked = TIER_STAKED
+            .may_load(storage, (pool_id, to_lock_seconds))?
+            .unwrap_or_default();
+        if capacity.saturating_sub(staked) < amount {
+            return Err(ContractError::CapacityExceeded {});
+        }
     }
+    sub_staked(storage, pool_id, from_lock_seconds, amount)?;
+    add_staked(storage, pool_id, to_lock_seconds, amount)?;
+    Ok(())
+}
+///////////////////////////////////////////////////////// this func is called for calculating the reward amount of one staking record //////////////////////////////////
+///
+///         The reward is only given once the lock period of the record has passed.
+///         The reward rate is the apy of the tier of the pool which has the same lock period.
+///         It does not touch the storage, so it can be used from queries as well.
+///         input params: tiers of the pool
+///                       staking record
+///                       amount the reward is based on(the reward base of the record)
+///                       current block time in seconds
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn calc_reward(
+    cfg: &Config,
+    tiers: &[Tier],
+    staker: &Position,
+    base: Uint128,
+    now: u64,
+) -> StdResult<Uint128> {
+    // a record can not be older than the block, but never underflow on it
+    let staked_time = now.saturating_sub(staker.last_time);
+    let mut reward_tot = Uint128::zero();
 
-    STAKERS.save(deps.storage, info.sender.clone(), &list)?;
+    if staked_time >= staker.lock_type {
+        if let Some(tier) = tiers.iter().find(|tier| tier.lock_seconds == staker.lock_type) {
+            reward_tot = checked_ratio(base, tier.apy as u128, MULTIPLE)?;
+        }
+    }
 
-    let msg = util::transfer_token_message(
-        Denom::Cw20(cfg.stake_token_address.clone()),
-        staked,
-        info.sender.clone(),
-    )?;
+    checked_ratio(
+        reward_tot,
+        cfg.reward_interval as u128,
+        constants::ONE_YEAR_SECONDS as u128,
+    )
+}
 
-    return Ok(Response::new().add_message(msg).add_attributes(vec![
-        attr("action", "unstake"),
-        attr("address", info.sender.clone()),
-        attr("staked_amount", Uint128::from(staked)),
-    ]));
+// amount * numerator / denominator without overflowing on the intermediate product
+fn checked_ratio(amount: Uint128, numerator: u128, denominator: u128) -> StdResult<Uint128> {
+    amount
+        .checked_multiply_ratio(numerator, denominator)
+        .map_err(|err| StdError::generic_err(err.to_string()))
 }
 
-///////////////////////////////////////////////////////// this func is called for checking ownership//////////////////////////////////
+///////////////////////////////////////////////////////// this func is called for getting the swap state of a lp pool //////////////////////////////////
 ///
-///         Owner is set when contract is instantiated.
-///         Using this function, we can authorize the ownership
+///         The swap state is only needed when the reward of the pool is based on the underlying reward asset.
+///         It is only read when staking, as the spot reserves can be moved within one transaction.
 ///     
-/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
-pub fn check_owner(deps: &DepsMut, info: &MessageInfo) -> Result<Response, ContractError> {
-    let cfg = CONFIG.load(deps.storage)?;
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+fn load_reward_swap_info(
+    querier: QuerierWrapper,
+    pool: &Pool,
+) -> StdResult<Option<WasmswapInfoResponse>> {
+    match &pool.lp {
+        Some(lp) if lp.reward_on_underlying => Ok(Some(util::get_swap_info(
+            querier,
+            lp.swap_address.clone(),
+        )?)),
+        _ => Ok(None),
+    }
+}
 
-    if info.sender != cfg.owner {
-        return Err(ContractError::Unauthorized {});
+fn reward_base(pool: &Pool, swap_info: &Option<WasmswapInfoResponse>, amount: Uint128) -> Uint128 {
+    match swap_info {
+        Some(swap_info) => {
+            let underlying = util::get_underlying_amount(swap_info, amount);
+            if underlying.token1_denom == pool.reward_asset {
+                underlying.token1_amount
+            } else {
+                underlying.token2_amount
+            }
+        }
+        None => amount,
     }
-    Ok(Response::new().add_attribute("action", "check_owner"))
 }
-///////////////////////////////////////////////////////// this func is called for checking enable state//////////////////////////////////
-///
-///         Enable state is set when contract is instantiated.
-///         The default vale is true.
-///     
-/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
 
-pub fn check_enabled(deps: &DepsMut, info: &MessageInfo) -> Result<Response, ContractError> {
-    let cfg = CONFIG.load(deps.storage)?;
-    if !cfg.enabled {
+// the last tier is used for unknown lock types
+fn select_tier(pool: &Pool, lock_type: u64) -> Result<&Tier, ContractError> {
+    pool.tiers
+        .get(lock_type as usize)
+        .or(pool.tiers.last())
+        .ok_or(ContractError::InvalidInput {})
+}
+
+fn load_pool(storage: &dyn Storage, pool_id: u64) -> Result<Pool, ContractError> {
+    POOLS
+        .may_load(storage, pool_id)?
+        .ok_or(ContractError::InvalidInput {})
+}
+
+fn load_enabled_pool(storage: &dyn Storage, pool_id: u64) -> Result<Pool, ContractError> {
+    let pool = load_pool(storage, pool_id)?;
+    if !pool.enabled {
         return Err(ContractError::Disabled {});
     }
-    Ok(Response::new().add_attribute("action", "check_enabled"))
+    Ok(pool)
 }
-///////////////////////////////////////////////////////// this func is called for updating the ownership//////////////////////////////////
+///////////////////////////////////////////////////////// this func is called for calculating the staking reward of a delegated record //////////////////////////////////
 ///
-///         Owner is set when contract is instantiated.
-///         if changing ownership is needed, we can use this function.
+///         The staking reward harvested from the validators is shared by the delegated amount.
+///         input params: staking record
+///                       current reward index
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn calc_delegation_reward(staker: &Position, reward_index: Decimal) -> Uint128 {
+    staker.amount.mul_floor(reward_index - staker.reward_index)
+}
+///////////////////////////////////////////////////////// this func is called for harvesting the staking reward from the validators //////////////////////////////////
+///
+///         The accumulated reward of every delegation is added to the reward index,
+///         and the messages withdrawing it to the contract are returned with the harvested amount.
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+fn harvest_delegation_rewards(
+    storage: &mut dyn Storage,
+    querier: QuerierWrapper,
+    env: &Env,
+) -> Result<(Vec<CosmosMsg>, Uint128), ContractError> {
+    let mut state = DELEGATION.load(storage)?;
+    let bonded_denom = querier.query_bonded_denom()?;
+
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    let mut harvested = Uint128::zero();
+    for delegation in querier.query_all_delegations(env.contract.address.clone())? {
+        if let Some(full) =
+            querier.query_delegation(env.contract.address.clone(), delegation.validator.clone())?
+        {
+            harvested += full
+                .accumulated_rewards
+                .iter()
+                .filter(|coin| coin.denom == bonded_denom)
+                .map(|coin| coin.amount)
+                .sum::<Uint128>();
+        }
+        msgs.push(
+            DistributionMsg::WithdrawDelegatorReward {
+                validator: delegation.validator,
+            }
+            .into(),
+        );
+    }
+
+    // the unstaked amount waiting for the undelegation has no staking record to get the reward
+    let staked = state
+        .total_delegated
+        .checked_sub(state.pending_undelegation)
+        .map_err(StdError::from)?;
+    if !harvested.is_zero() && !staked.is_zero() {
+        state.reward_index += Decimal::from_ratio(harvested, staked);
+        state.unpaid_reward = state
+            .unpaid_reward
+            .checked_add(harvested)
+            .map_err(StdError::from)?;
+        DELEGATION.save(storage, &state)?;
+    }
+
+    Ok((msgs, harvested))
+}
+///////////////////////////////////////////////////////// this func is called for delegating the native stake //////////////////////////////////
+///
+///         The amount is split equally to the validators set by the owner.
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+fn delegate(
+    storage: &mut dyn Storage,
+    asset: &Denom,
+    amount: Uint128,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    let cfg = CONFIG.load(storage)?;
+    let denom = match asset {
+        Denom::Native(denom) => denom.clone(),
+        Denom::Cw20(_) => return Err(ContractError::UnacceptableToken {}),
+    };
+    if cfg.validators.is_empty() {
+        return Err(ContractError::NoValidators {});
+    }
+
+    let count = Uint128::from(cfg.validators.len() as u128);
+    let share = amount / count;
+    let remainder = amount - share * count;
+
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    for (index, validator) in cfg.validators.into_iter().enumerate() {
+        // the remainder goes to the first validator
+        let delegate_amount = if index == 0 { share + remainder } else { share };
+        if delegate_amount.is_zero() {
+            continue;
+        }
+        msgs.push(
+            StakingMsg::Delegate {
+                validator,
+                amount: Coin {
+                    denom: denom.clone(),
+                    amount: delegate_amount,
+                },
+            }
+            .into(),
+        );
+    }
+
+    DELEGATION.update(storage, |mut state| -> StdResult<_> {
+        state.total_delegated += amount;
+        Ok(state)
+    })?;
+
+    Ok(msgs)
+}
+///////////////////////////////////////////////////////// this func is called for adding the unstaked native stake to the undelegation batch //////////////////////////////////
+///
+///         The chain only allows a few unbonding entries for a validator, so the unstaked amount is undelegated
+///         with the other unstakes of the epoch. The amount is added to the unbondings of the address with the batch.
+///         input params: address of the staker
+///                       unstaked amount
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+fn queue_undelegation(
+    storage: &mut dyn Storage,
+    address: &Addr,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    let mut state = DELEGATION.load(storage)?;
+    state.pending_undelegation = state
+        .pending_undelegation
+        .checked_add(amount)
+        .map_err(StdError::from)?;
+    DELEGATION.save(storage, &state)?;
+
+    let mut unbondings = UNBONDINGS
+        .may_load(storage, address.clone())?
+        .unwrap_or_default();
+    match unbondings
+        .iter_mut()
+        .find(|unbonding| unbonding.batch_id == state.batch_id)
+    {
+        Some(unbonding) => {
+            unbonding.amount = unbonding.amount.checked_add(amount).map_err(StdError::from)?
+        }
+        None => unbondings.push(Unbonding {
+            batch_id: state.batch_id,
+            amount,
+        }),
+    }
+    UNBONDINGS.save(storage, address.clone(), &unbondings)?;
+    Ok(())
+}
+///////////////////////////////////////////////////////// this func is called for undelegating the batch of the unstaked native stake //////////////////////////////////
+///
+///         The batch is undelegated once per epoch, so a validator never gets more unbonding entries than the chain allows.
+///         The amount is undelegated from the current delegations of the contract in order.
+///         After a slash of the validators the delegations are less than the delegated amount,
+///         so the batch only takes its share of the delegations and the unbondings of the batch are paid by it.
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+fn undelegate_batch(
+    storage: &mut dyn Storage,
+    querier: QuerierWrapper,
+    env: &Env,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    let mut state = DELEGATION.load(storage)?;
+    let now = env.block.time.seconds();
+    let next_undelegation_time = state
+        .last_undelegation_time
+        .saturating_add(constants::UNDELEGATION_EPOCH_SECONDS);
+    if state.pending_undelegation.is_zero() || now < next_undelegation_time {
+        return Ok(vec![]);
+    }
+
+    let delegations = querier.query_all_delegations(env.contract.address.clone())?;
+    let delegated = delegations
+        .iter()
+        .try_fold(Uint128::zero(), |total, delegation| {
+            total.checked_add(delegation.amount.amount)
+        })
+        .map_err(StdError::from)?;
+    let amount = if delegated < state.total_delegated {
+        state
+            .pending_undelegation
+            .multiply_ratio(delegated, state.total_delegated)
+    } else {
+        state.pending_undelegation
+    };
+
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    let mut remaining = amount;
+    for delegation in delegations {
+        if remaining.is_zero() {
+            break;
+        }
+        let undelegate_amount = remaining.min(delegation.amount.amount);
+        if undelegate_amount.is_zero() {
+            continue;
+        }
+        remaining -= undelegate_amount;
+        msgs.push(
+            StakingMsg::Undelegate {
+                validator: delegation.validator,
+                amount: Coin {
+                    denom: delegation.amount.denom,
+                    amount: undelegate_amount,
+                },
+            }
+            .into(),
+        );
+    }
+
+    UNDELEGATION_BATCHES.save(
+        storage,
+        state.batch_id,
+        &UndelegationBatch {
+            amount: state.pending_undelegation,
+            undelegated: amount,
+            release_time: now + constants::UNBONDING_SECONDS,
+        },
+    )?;
+    state.total_delegated = state
+        .total_delegated
+        .checked_sub(state.pending_undelegation)
+        .map_err(StdError::from)?;
+    state.pending_undelegation = Uint128::zero();
+    state.batch_id += 1;
+    state.last_undelegation_time = now;
+    DELEGATION.save(storage, &state)?;
+
+    Ok(msgs)
+}
+
+// adds the batches which are released by the chain to the unbonded amount kept for the withdrawal
+fn release_unbonded(
+    storage: &dyn Storage,
+    state: &mut DelegationState,
+    now: u64,
+) -> StdResult<()> {
+    while state.next_release_batch < state.batch_id {
+        let batch = UNDELEGATION_BATCHES.may_load(storage, state.next_release_batch)?;
+        if let Some(batch) = batch {
+            if batch.release_time > now {
+                break;
+            }
+            state.unbonded = state.unbonded.checked_add(batch.undelegated)?;
+        }
+        state.next_release_batch += 1;
+    }
+    Ok(())
+}
+///////////////////////////////////////////////////////// this func is called for getting the amount which is kept for the stakers //////////////////////////////////
+///
+///         The stake of the pools which are not delegated, the deposit of the airdrop stages which is not claimed yet
+///         and the claimed reward which is still vesting are in the balance of the asset with the reward.
+///         The unbonded stake waiting for the withdrawal and the harvested staking reward which is not paid yet
+///         are in the balance of the bonded denom too. So none of them can be paid as the reward.
+///         input params: asset of the balance
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+fn reserved_amount(
+    storage: &mut dyn Storage,
+    querier: QuerierWrapper,
+    env: &Env,
+    asset: &Denom,
+) -> Result<Uint128, ContractError> {
+    let mut reserved = REWARD_VESTING_TOTAL
+        .may_load(storage, &util::denom_key(asset))?
+        .unwrap_or_default();
+
+    let pools = POOLS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(u64, Pool)>>>()?;
+    for (pool_id, pool) in pools {
+        if !pool.delegated && pool.stake_asset == *asset {
+            let staked = POOL_STAKED.may_load(storage, pool_id)?.unwrap_or_default();
+            reserved = reserved.checked_add(staked).map_err(StdError::from)?;
+        }
+    }
+    for item in AIRDROP_STAGES.range(storage, None, None, Order::Ascending) {
+        let (_, airdrop) = item?;
+        if airdrop.asset == *asset {
+            reserved = reserved
+                .checked_add(airdrop.total_amount - airdrop.claimed_amount)
+                .map_err(StdError::from)?;
+        }
+    }
+
+    if matches!(asset, Denom::Native(denom) if *denom == querier.query_bonded_denom()?) {
+        let mut state = DELEGATION.load(storage)?;
+        release_unbonded(storage, &mut state, env.block.time.seconds())?;
+        DELEGATION.save(storage, &state)?;
+        reserved = reserved
+            .checked_add(state.unbonded)
+            .and_then(|reserved| reserved.checked_add(state.unpaid_reward))
+            .map_err(StdError::from)?;
+    }
+    Ok(reserved)
+}
+///////////////////////////////////////////////////////// this func is called for getting the balance which can be paid as the reward //////////////////////////////////
+///
+///         The harvested staking reward is not in the balance until the harvest messages are executed, so it is added.
+///         input params: asset of the reward
+///                       harvested amount
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+fn available_reward(
+    storage: &mut dyn Storage,
+    querier: QuerierWrapper,
+    env: &Env,
+    asset: &Denom,
+    harvested: Uint128,
+) -> Result<Uint128, ContractError> {
+    let reserved = reserved_amount(storage, querier, env, asset)?;
+    Ok(
+        util::get_token_amount(querier, asset.clone(), env.contract.address.clone())?
+            .checked_add(harvested)
+            .map_err(StdError::from)?
+            .saturating_sub(reserved),
+    )
+}
+
+// the delegation reward paid to the stakers, or forfeited by them, is not kept anymore
+fn pay_delegation_reward(storage: &mut dyn Storage, amount: Uint128) -> StdResult<()> {
+    DELEGATION.update(storage, |mut state| -> StdResult<_> {
+        // the weighted reward index of merged records can round the paid reward up by a few units
+        state.unpaid_reward = state.unpaid_reward.saturating_sub(amount);
+        Ok(state)
+    })?;
+    Ok(())
+}
+///////////////////////////////////////////////////////// this func is called for calculating the reward amount  //////////////////////////////////
+///
+///         Only the staking records of the page claimed at once are updated.
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn update_reward(
+    storage: &mut dyn Storage,
+    env: Env,
+    pool_id: u64,
+    address: Addr,
+) -> Result<Uint128, ContractError> {
+    let cfg = CONFIG.load(storage)?;
+    let (mut exists, _) = load_positions_page(storage, &cfg, pool_id, &address)?;
+    let pool = load_pool(storage, pool_id)?;
+    let mut total_reward = Uint128::zero();
+
+    for staker in exists.iter_mut() {
+        let now = env.block.time.seconds();
+        let reward = calc_reward(&cfg, &pool.tiers, staker, staker.reward_base, now)?;
+        staker.reward = staker.accrued.checked_add(reward).map_err(StdError::from)?;
+        total_reward = total_reward.checked_add(staker.reward).map_err(StdError::from)?;
+    }
+
+    save_positions(storage, &exists)?;
+
+    Ok(total_reward)
+}
+///////////////////////////////////////////////////////// this func is called when we click reward button on frontend//////////////////////////////////
+///
+///         input params: customer's wallet address
+///                       pool id
+///                       juno reward flag(This is true when rank is bigger than 500, if this is true, customer can get juno reward, if false, cutomer can get only hole reward)
+///                       artists wallet percent
+///                       burn wallet percent
+///                       charity wallet percent
+///                       my wallet percent = 100 - artists_percent - burn_percent - charity_percent
+///
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn execute_claim_reward(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    pool_id: u64,
+    reward_distribution: RewardDistribution,
+) -> Result<Response, ContractError> {
+    check_enabled(&deps, &info)?;
+    let cfg = CONFIG.load(deps.storage)?;
+    if cfg.claim_paused {
+        return Err(ContractError::ClaimPaused {});
+    }
+    let pool = load_enabled_pool(deps.storage, pool_id)?;
+
+    // the shares can not give away more than the reward
+    let distributed_percent = reward_distribution
+        .charity
+        .checked_add(reward_distribution.burn)
+        .and_then(|percent| percent.checked_add(reward_distribution.artists))
+        .filter(|percent| *percent <= 100)
+        .ok_or(ContractError::InvalidInput {})?;
+
+    let cw20_reward = update_reward(
+        deps.storage,
+        env.clone(),
+        pool_id,
+        info.sender.clone(),
+    )?;
+
+    let (mut list, more) = load_positions_page(deps.storage, &cfg, pool_id, &info.sender)?;
+
+    // the staking reward of delegated pools is given with the juno reward
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    let mut delegation_reward = Uint128::zero();
+    let mut harvested = Uint128::zero();
+    if pool.delegated && reward_distribution.juno_reward {
+        (msgs, harvested) = harvest_delegation_rewards(deps.storage, deps.querier, &env)?;
+        let reward_index = DELEGATION.load(deps.storage)?.reward_index;
+        for staker in list.iter_mut() {
+            delegation_reward += calc_delegation_reward(staker, reward_index);
+            staker.reward_index = reward_index;
+        }
+        pay_delegation_reward(deps.storage, delegation_reward)?;
+    }
+
+    // // neet to change reward to ujuno
+    // // test for 1/1000 juno
+    // // let juno_reward = querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+    // //     contract_addr: pool_address.clone().into(),
+    // //     msg: to_json_binary(&WasmswapQueryMsg::Token2ForToken1Price {
+    // //         token2_amount: amount
+    // //     })?,
+    // // }))?;
+    let juno_reward = cw20_reward.checked_add(delegation_reward).map_err(StdError::from)?; //for test
+    
+    // the stake and the reward kept for the stakers can not be paid as the reward,
+    // and the juno reward is only paid if the juno reward flag is set
+    let mut payouts: Vec<(Denom, Uint128)> = vec![(pool.reward_asset.clone(), cw20_reward)];
+    if reward_distribution.juno_reward {
+        match payouts.iter_mut().find(|(asset, _)| *asset == pool.native_reward_asset) {
+            Some((_, amount)) => {
+                *amount = amount.checked_add(juno_reward).map_err(StdError::from)?
+            }
+            None => payouts.push((pool.native_reward_asset.clone(), juno_reward)),
+        }
+    }
+    for (asset, amount) in payouts.iter().filter(|(_, amount)| !amount.is_zero()) {
+        let harvested = if *asset == pool.native_reward_asset {
+            harvested
+        } else {
+            Uint128::zero()
+        };
+        if available_reward(deps.storage, deps.querier, &env, asset, harvested)? < *amount {
+            return Err(ContractError::NotEnoughReward {});
+        }
+    }
+
+    for staker in list.iter_mut() {
+        staker.accrued = Uint128::zero();
+        staker.last_time = env.block.time.seconds();
+    }
+    save_positions(deps.storage, &list)?;
+
+    // the reward vests linearly after claim if the vesting period is set
+    if !cw20_reward.is_zero() {
+        if cfg.reward_vesting_seconds == 0 {
+            msgs.push(util::transfer_token_message(
+                pool.reward_asset.clone(),
+                cw20_reward,
+                info.sender.clone(),
+            )?);
+        } else {
+            let mut vestings = REWARD_VESTING
+                .may_load(deps.storage, info.sender.clone())?
+                .unwrap_or_default();
+            vestings.push(RewardVesting {
+                asset: pool.reward_asset.clone(),
+                amount: cw20_reward,
+                withdrawn: Uint128::zero(),
+                start_time: env.block.time.seconds(),
+                end_time: env.block.time.seconds().saturating_add(cfg.reward_vesting_seconds),
+            });
+            REWARD_VESTING.save(deps.storage, info.sender.clone(), &vestings)?;
+            REWARD_VESTING_TOTAL.update(
+                deps.storage,
+                &util::denom_key(&pool.reward_asset),
+                |total| -> StdResult<_> {
+                    Ok(total.unwrap_or_default().checked_add(cw20_reward)?)
+                },
+            )?;
+        }
+    }
+
+    if reward_distribution.juno_reward {
+        if reward_distribution.charity != 0u64 {
+            let reward_charity =
+                checked_ratio(juno_reward, reward_distribution.charity as u128, 100)?;
+            if !reward_charity.is_zero() {
+                msgs.push(util::transfer_token_message(
+                    pool.native_reward_asset.clone(),
+                    reward_charity,
+                    cfg.reward_charity_address.clone(),
+                )?);
+            }
+        }
+
+        if reward_distribution.burn != 0u64 {
+            let reward_burn =
+                checked_ratio(juno_reward, reward_distribution.burn as u128, 100)?;
+            if !reward_burn.is_zero() {
+                msgs.push(util::transfer_token_message(
+                    pool.native_reward_asset.clone(),
+                    reward_burn,
+                    cfg.reward_burn_address.clone(),
+                )?);
+            }
+        }
+
+        if reward_distribution.artists != 0u64 {
+            let reward_artists =
+                checked_ratio(juno_reward, reward_distribution.artists as u128, 100)?;
+            if !reward_artists.is_zero() {
+                msgs.push(util::transfer_token_message(
+                    pool.native_reward_asset.clone(),
+                    reward_artists,
+                    cfg.reward_artists_address.clone(),
+                )?);
+            }
+        }
+
+        if distributed_percent < 100 {
+            let reward_user_rate = 100 - distributed_percent;
+            let reward_user = checked_ratio(juno_reward, reward_user_rate as u128, 100)?;
+            if !reward_user.is_zero() {
+                msgs.push(util::transfer_token_message(
+                    pool.native_reward_asset.clone(),
+                    reward_user,
+                    info.sender.clone(),
+                )?);
+            }
+        }
+    }
+
+    // End
+
+    Ok(Response::new().add_messages(msgs).add_attributes(vec![
+        attr("action", "claim_reward"),
+        attr("pool_id", pool_id.to_string()),
+        attr("address", info.sender.clone()),
+        attr("reward_amount", cw20_reward),
+        attr("reward_vesting", (cfg.reward_vesting_seconds != 0).to_string()),
+        attr("more_positions", more.to_string()),
+    ]))
+}
+///////////////////////////////////////////////////////// this func is called when we click unstake button on frontend//////////////////////////////////
+///
+///         Using this function, we can unstake all staked token of the pool
+///         At most max_positions_per_address records are unstaked at once, the rest with the next call.
+///         Every staking record is returned in the asset it was staked in.
+///         The stake of delegated pools is undelegated with the batch of the epoch and can be withdrawn
+///         after the unbonding period, and the staking reward of the records is paid to the staker at once.
+///         input params: pool id
+///         
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn execute_unstake(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    pool_id: u64,
+) -> Result<Response, ContractError> {
+    check_enabled(&deps, &info)?;
+    let cfg = CONFIG.load(deps.storage)?;
+    if cfg.unstake_paused {
+        return Err(ContractError::UnstakePaused {});
+    }
+    // a disabled pool takes no new stakes, but the principal can always be taken out
+    let pool = load_pool(deps.storage, pool_id)?;
+
+    let (list, more) = load_positions_page(deps.storage, &cfg, pool_id, &info.sender)?;
+
+    if pool.delegated {
+        let staked: Uint128 = list.iter().map(|staker| staker.amount).sum();
+        let (mut msgs, _) = harvest_delegation_rewards(deps.storage, deps.querier, &env)?;
+
+        // the staking reward of the records is paid before they are removed
+        let reward_index = DELEGATION.load(deps.storage)?.reward_index;
+        let delegation_reward = list.iter().try_fold(Uint128::zero(), |total, staker| {
+            total
+                .checked_add(calc_delegation_reward(staker, reward_index))
+                .map_err(StdError::from)
+        })?;
+        pay_delegation_reward(deps.storage, delegation_reward)?;
+
+        queue_undelegation(deps.storage, &info.sender, staked)?;
+        msgs.extend(undelegate_batch(deps.storage, deps.querier, &env)?);
+        if !delegation_reward.is_zero() {
+            msgs.push(util::transfer_token_message(
+                pool.native_reward_asset.clone(),
+                delegation_reward,
+                info.sender.clone(),
+            )?);
+        }
+
+        for staker in list.iter() {
+            sub_staked(deps.storage, pool_id, staker.lock_type, staker.amount)?;
+            remove_position(deps.storage, staker)?;
+        }
+
+        return Ok(Response::new().add_messages(msgs).add_attributes(vec![
+            attr("action", "unstake"),
+            attr("pool_id", pool_id.to_string()),
+            attr("address", info.sender.clone()),
+            attr("unbonding_amount", staked),
+            attr("delegation_reward", delegation_reward),
+            attr("more_positions", more.to_string()),
+        ]));
+    }
+
+    // check if user can unstake this record
+    // env.block.time.seconds(), record.stake_time
+    let mut staked: Vec<(Denom, Uint128)> = vec![];
+
+    for staker in list.iter() {
+        match staked.iter_mut().find(|(asset, _)| *asset == staker.asset) {
+            Some((_, amount)) => *amount += staker.amount,
+            None => staked.push((staker.asset.clone(), staker.amount)),
+        }
+    }
+
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    let mut attrs = vec![
+        attr("action", "unstake"),
+        attr("pool_id", pool_id.to_string()),
+        attr("address", info.sender.clone()),
+        attr("more_positions", more.to_string()),
+    ];
+
+    for (asset, amount) in staked {
+        let tot_staked = util::get_token_amount(
+            deps.querier,
+            asset.clone(),
+            env.contract.address.clone(),
+        )?;
+
+        if tot_staked < amount {
+            return Err(ContractError::NotEnoughStake {});
+        }
+
+        attrs.push(attr("asset", util::denom_key(&asset)));
+        attrs.push(attr("staked_amount", amount));
+        msgs.push(util::transfer_token_message(
+            asset,
+            amount,
+            info.sender.clone(),
+        )?);
+    }
+
+    for staker in list.iter() {
+        sub_staked(deps.storage, pool_id, staker.lock_type, staker.amount)?;
+        remove_position(deps.storage, staker)?;
+    }
+
+    Ok(Response::new().add_messages(msgs).add_attributes(attrs))
+}
+///////////////////////////////////////////////////////// this func is called when the user withdraws the stake in emergency mode //////////////////////////////////
+///
+///         This is the escape hatch while the owner has set the emergency mode.
+///         The staking records of every pool are removed ignoring the enable state, the pause flags and the locks.
+///         Only the principal is returned, the reward which is not claimed yet is forfeited.
+///         At most max_positions_per_address records are withdrawn at once, the rest with the next call.
+///         The stake of delegated pools is undelegated with the batch of the epoch and can be withdrawn
+///         after the unbonding period.
+///         input params: none
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn execute_emergency_withdraw(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    if !cfg.emergency {
+        return Err(ContractError::NotEmergency {});
+    }
+
+    let limit = cfg.max_positions_per_address as usize;
+    let mut list = positions()
+        .prefix(info.sender.clone())
+        .range(deps.storage, None, None, Order::Ascending)
+        .take(limit + 1)
+        .map(|item| item.map(|(_, position)| position))
+        .collect::<StdResult<Vec<Position>>>()?;
+    if list.is_empty() {
+        return Err(ContractError::NoStaked {});
+    }
+    let more = list.len() > limit;
+    list.truncate(limit);
+
+    let mut returned: Vec<(Denom, Uint128)> = vec![];
+    let mut delegated: Vec<&Position> = vec![];
+    for position in list.iter() {
+        let pool = load_pool(deps.storage, position.pool_id)?;
+        if pool.delegated {
+            delegated.push(position);
+        } else {
+            match returned.iter_mut().find(|(asset, _)| *asset == position.asset) {
+                Some((_, amount)) => *amount += position.amount,
+                None => returned.push((position.asset.clone(), position.amount)),
+            }
+        }
+        sub_staked(deps.storage, position.pool_id, position.lock_type, position.amount)?;
+        remove_position(deps.storage, position)?;
+    }
+
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    let mut attrs = vec![
+        attr("action", "emergency_withdraw"),
+        attr("address", info.sender.clone()),
+        attr("positions", list.len().to_string()),
+        attr("more_positions", more.to_string()),
+    ];
+
+    if !delegated.is_empty() {
+        // the reward index is kept right for the other stakers of delegated pools
+        let (harvest_msgs, _) = harvest_delegation_rewards(deps.storage, deps.querier, &env)?;
+        msgs.extend(harvest_msgs);
+
+        // the staking reward of the records is forfeited with the reward
+        let reward_index = DELEGATION.load(deps.storage)?.reward_index;
+        let mut unbonding = Uint128::zero();
+        let mut forfeited = Uint128::zero();
+        for position in delegated {
+            unbonding = unbonding.checked_add(position.amount).map_err(StdError::from)?;
+            forfeited = forfeited
+                .checked_add(calc_delegation_reward(position, reward_index))
+                .map_err(StdError::from)?;
+        }
+        pay_delegation_reward(deps.storage, forfeited)?;
+
+        queue_undelegation(deps.storage, &info.sender, unbonding)?;
+        msgs.extend(undelegate_batch(deps.storage, deps.querier, &env)?);
+        attrs.push(attr("unbonding_amount", unbonding));
+    }
+
+    for (asset, amount) in returned {
+        attrs.push(attr("asset", util::denom_key(&asset)));
+        attrs.push(attr("amount", amount));
+        msgs.push(util::transfer_token_message(
+            asset,
+            amount,
+            info.sender.clone(),
+        )?);
+    }
+
+    Ok(Response::new().add_messages(msgs).add_attributes(attrs))
+}
+
+///////////////////////////////////////////////////////// this func is called for harvesting the staking reward of delegated pools //////////////////////////////////
+///
+///         Anyone can call this function. The harvested reward is given to the stakers of delegated pools
+///         with the juno reward when they claim, split by their reward distribution.
+///         The unstaked batch is undelegated with it once the epoch has passed.
+///         input params: none
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn execute_harvest_delegation_rewards(
+    deps: DepsMut,
+    env: Env,
+) -> Result<Response, ContractError> {
+    let (mut msgs, harvested) = harvest_delegation_rewards(deps.storage, deps.querier, &env)?;
+    msgs.extend(undelegate_batch(deps.storage, deps.querier, &env)?);
+    let state = DELEGATION.load(deps.storage)?;
+
+    Ok(Response::new().add_messages(msgs).add_attributes(vec![
+        attr("action", "harvest_delegation_rewards"),
+        attr("harvested_amount", harvested),
+        attr("reward_index", state.reward_index.to_string()),
+        attr("pending_undelegation", state.pending_undelegation),
+    ]))
+}
+///////////////////////////////////////////////////////// this func is called when we click withdraw button for the unbonded stake on frontend//////////////////////////////////
+///
+///         Using this function, we can withdraw the stake of delegated pools after the unbonding period.
+///         input params: none
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn execute_withdraw_unbonded(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let mut msgs = undelegate_batch(deps.storage, deps.querier, &env)?;
+    let mut state = DELEGATION.load(deps.storage)?;
+    let now = env.block.time.seconds();
+    release_unbonded(deps.storage, &mut state, now)?;
+
+    let unbondings = UNBONDINGS
+        .may_load(deps.storage, info.sender.clone())?
+        .unwrap_or_default();
+
+    // every unbonding is paid by its share of the amount undelegated for the batch
+    let mut amount = Uint128::zero();
+    let mut released = 0usize;
+    let mut remaining: Vec<Unbonding> = vec![];
+    for unbonding in unbondings {
+        let mut batch = match UNDELEGATION_BATCHES.may_load(deps.storage, unbonding.batch_id)? {
+            Some(batch) if batch.release_time <= now => batch,
+            _ => {
+                remaining.push(unbonding);
+                continue;
+            }
+        };
+        let paid = batch
+            .undelegated
+            .multiply_ratio(unbonding.amount, batch.amount);
+        batch.amount = batch.amount.checked_sub(unbonding.amount).map_err(StdError::from)?;
+        batch.undelegated = batch.undelegated.checked_sub(paid).map_err(StdError::from)?;
+        if batch.amount.is_zero() {
+            UNDELEGATION_BATCHES.remove(deps.storage, unbonding.batch_id);
+        } else {
+            UNDELEGATION_BATCHES.save(deps.storage, unbonding.batch_id, &batch)?;
+        }
+        amount = amount.checked_add(paid).map_err(StdError::from)?;
+        released += 1;
+    }
+    if released == 0 {
+        return Err(ContractError::NotCreatedUnstaking {});
+    }
+
+    let bonded_denom = Denom::Native(deps.querier.query_bonded_denom()?);
+    let tot = util::get_token_amount(
+        deps.querier,
+        bonded_denom.clone(),
+        env.contract.address.clone(),
+    )?;
+    if tot < amount {
+        return Err(ContractError::NotEnoughStake {});
+    }
+
+    state.unbonded = state.unbonded.checked_sub(amount).map_err(StdError::from)?;
+    DELEGATION.save(deps.storage, &state)?;
+    if remaining.is_empty() {
+        UNBONDINGS.remove(deps.storage, info.sender.clone());
+    } else {
+        UNBONDINGS.save(deps.storage, info.sender.clone(), &remaining)?;
+    }
+
+    if !amount.is_zero() {
+        msgs.push(util::transfer_token_message(
+            bonded_denom,
+            amount,
+            info.sender.clone(),
+        )?);
+    }
+
+    Ok(Response::new().add_messages(msgs).add_attributes(vec![
+        attr("action", "withdraw_unbonded"),
+        attr("address", info.sender.clone()),
+        attr("amount", amount),
+    ]))
+}
+///////////////////////////////////////////////////////// this func is called for calculating the vested amount of a reward //////////////////////////////////
+///
+///         The reward vests linearly from the claim time to the end of the vesting period.
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn calc_vested(vesting: &RewardVesting, now: u64) -> Uint128 {
+    if now >= vesting.end_time {
+        return vesting.amount;
+    }
+    if now <= vesting.start_time {
+        return Uint128::zero();
+    }
+    vesting.amount.multiply_ratio(
+        now - vesting.start_time,
+        vesting.end_time - vesting.start_time,
+    )
+}
+///////////////////////////////////////////////////////// this func is called when we click withdraw button for the vested reward on frontend//////////////////////////////////
+///
+///         Using this function, we can withdraw the vested part of the claimed rewards.
+///         input params: none
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn execute_withdraw_vested(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let mut vestings = REWARD_VESTING
+        .may_load(deps.storage, info.sender.clone())?
+        .unwrap_or_default();
+    let now = env.block.time.seconds();
+
+    let mut withdrawn: Vec<(Denom, Uint128)> = vec![];
+    for vesting in vestings.iter_mut() {
+        let amount = calc_vested(vesting, now) - vesting.withdrawn;
+        if amount.is_zero() {
+            continue;
+        }
+        vesting.withdrawn += amount;
+        match withdrawn.iter_mut().find(|(asset, _)| *asset == vesting.asset) {
+            Some((_, total)) => *total += amount,
+            None => withdrawn.push((vesting.asset.clone(), amount)),
+        }
+    }
+    if withdrawn.is_empty() {
+        return Err(ContractError::NoReward {});
+    }
+
+    vestings.retain(|vesting| vesting.withdrawn < vesting.amount);
+    REWARD_VESTING.save(deps.storage, info.sender.clone(), &vestings)?;
+
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    let mut attrs = vec![
+        attr("action", "withdraw_vested"),
+        attr("address", info.sender.clone()),
+    ];
+    for (asset, amount) in withdrawn {
+        REWARD_VESTING_TOTAL.update(
+            deps.storage,
+            &util::denom_key(&asset),
+            |total| -> StdResult<_> {
+                Ok(total.unwrap_or_default().checked_sub(amount)?)
+            },
+        )?;
+        attrs.push(attr("asset", util::denom_key(&asset)));
+        attrs.push(attr("amount", amount));
+        msgs.push(util::transfer_token_message(asset, amount, info.sender.clone())?);
+    }
+
+    Ok(Response::new().add_messages(msgs).add_attributes(attrs))
+}
+
+///////////////////////////////////////////////////////// this func is called when user moves a staking record to another wallet //////////////////////////////////
+///
+///         The staking record is moved without unlocking, so the lock time and the reward are preserved.
+///         input params: position id
+///                       recipient address
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn execute_transfer_position(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    position_id: u64,
+    recipient: String,
+) -> Result<Response, ContractError> {
+    check_enabled(&deps, &info)?;
+    let recipient = deps.api.addr_validate(&recipient)?;
+    if recipient == info.sender {
+        return Err(ContractError::InvalidInput {});
+    }
+
+    let position = load_position(deps.storage, position_id)?;
+    if position.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    transfer_position(&mut deps, &env, &info.sender, &recipient, position_id)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "transfer_position"),
+        attr("pool_id", position.pool_id.to_string()),
+        attr("position_id", position_id.to_string()),
+        attr("from", info.sender.clone()),
+        attr("to", recipient),
+        attr("amount", position.amount),
+        attr("lock_type", position.lock_type.to_string()),
+        attr("last_time", position.last_time.to_string()),
+    ]))
+}
+///////////////////////////////////////////////////////// this func is called when user extends the lock of a staking record //////////////////////////////////
+///
+///         The staking record is moved to a tier with a longer lock period to get the higher apy without unstaking.
+///         The reward of the current lock is settled first, and the new lock starts from now.
+///         input params: position id
+///                       new lock_type (index of the tier of the pool)
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn execute_extend_lock(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    position_id: u64,
+    new_lock_type: u64,
+) -> Result<Response, ContractError> {
+    check_enabled(&deps, &info)?;
+    let mut position = load_position(deps.storage, position_id)?;
+    if position.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let cfg = CONFIG.load(deps.storage)?;
+    let pool = load_enabled_pool(deps.storage, position.pool_id)?;
+    let lock_seconds = select_tier(&pool, new_lock_type)?.lock_seconds;
+    if lock_seconds <= position.lock_type {
+        return Err(ContractError::LockNotExtended {
+            current: position.lock_type,
+            requested: lock_seconds,
+        });
+    }
+
+    move_staked_tier(
+        deps.storage,
+        &pool,
+        position.pool_id,
+        position.lock_type,
+        lock_seconds,
+        position.amount,
+    )?;
+
+    let now = env.block.time.seconds();
+    let settled = calc_reward(&cfg, &pool.tiers, &position, position.reward_base, now)?;
+
+    position.accrued = position
+        .accrued
+        .checked_add(settled)
+        .map_err(StdError::from)?;
+    position.last_time = now;
+    position.lock_type = lock_seconds;
+    positions().save(deps.storage, (position.owner.clone(), position_id), &position)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "extend_lock"),
+        attr("pool_id", position.pool_id.to_string()),
+        attr("position_id", position_id.to_string()),
+        attr("address", info.sender.clone()),
+        attr("lock_type", lock_seconds.to_string()),
+        attr("settled_reward", settled),
+    ]))
+}
+///////////////////////////////////////////////////////// this func is called when user merges staking records into one //////////////////////////////////
+///
+///         The records must be in the same pool. The merged record gets the longest lock period of the records,
+///         and the reward of the matured records is settled first.
+///         If no record is upgraded to a longer lock and none is matured, the lock ends at the latest end of the records,
+///         otherwise the lock starts from now.
+///         The first record is kept as the merged record, the others are removed.
+///         input params: position ids
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn execute_merge_positions(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    position_ids: Vec<u64>,
+) -> Result<Response, ContractError> {
+    check_enabled(&deps, &info)?;
+    let mut ids = position_ids.clone();
+    ids.sort_unstable();
+    ids.dedup();
+    if position_ids.len() < 2 || ids.len() != position_ids.len() {
+        return Err(ContractError::InvalidInput {});
+    }
+
+    let list = position_ids
+        .iter()
+        .map(|position_id| load_position(deps.storage, *position_id))
+        .collect::<Result<Vec<Position>, ContractError>>()?;
+    if list.iter().any(|position| position.owner != info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    let pool_id = list[0].pool_id;
+    if list.iter().any(|position| position.pool_id != pool_id) {
+        return Err(ContractError::InvalidInput {});
+    }
+
+    let cfg = CONFIG.load(deps.storage)?;
+    let pool = load_enabled_pool(deps.storage, pool_id)?;
+    let now = env.block.time.seconds();
+
+    let lock_seconds = list
+        .iter()
+        .map(|position| position.lock_type)
+        .max()
+        .unwrap_or_default();
+    let total = list.iter().try_fold(Uint128::zero(), |total, position| {
+        total.checked_add(position.amount).map_err(StdError::from)
+    })?;
+    let total_base = list.iter().try_fold(Uint128::zero(), |total, position| {
+        total.checked_add(position.reward_base).map_err(StdError::from)
+    })?;
+
+    let mut settled = Uint128::zero();
+    let mut accrued = Uint128::zero();
+    let mut lock_end = 0u64;
+    let mut reward_index = Decimal::zero();
+    for position in list.iter() {
+        let reward = calc_reward(&cfg, &pool.tiers, position, position.reward_base, now)?;
+        settled = settled.checked_add(reward).map_err(StdError::from)?;
+        accrued = accrued
+            .checked_add(position.accrued)
+            .and_then(|accrued| accrued.checked_add(reward))
+            .map_err(StdError::from)?;
+
+        // a matured or upgraded record starts a new lock
+        let end = position
+            .last_time
+            .checked_add(position.lock_type)
+            .ok_or(ContractError::InvalidInput {})?;
+        if end <= now || position.lock_type < lock_seconds {
+            let new_end = now
+                .checked_add(lock_seconds)
+                .ok_or(ContractError::InvalidInput {})?;
+            lock_end = lock_end.max(new_end);
+        } else {
+            lock_end = lock_end.max(end);
+        }
+
+        // the delegation reward of every record is kept with the weighted reward index
+        reward_index = Decimal::from_ratio(position.amount, total)
+            .checked_mul(position.reward_index)
+            .and_then(|weighted| reward_index.checked_add(weighted))
+            .map_err(StdError::from)?;
+
+        if position.lock_type < lock_seconds {
+            move_staked_tier(
+                deps.storage,
+                &pool,
+                pool_id,
+                position.lock_type,
+                lock_seconds,
+                position.amount,
+            )?;
+        }
+    }
+
+    let mut merged = list[0].clone();
+    merged.amount = total;
+    merged.reward_base = total_base;
+    merged.reward = Uint128::zero();
+    merged.accrued = accrued;
+    merged.lock_type = lock_seconds;
+    merged.last_time = lock_end - lock_seconds;
+    merged.reward_index = reward_index;
+    merged.received = list.iter().all(|position| position.received);
+
+    for position in list.iter().skip(1) {
+        remove_position(deps.storage, position)?;
+    }
+    positions().save(deps.storage, (merged.owner.clone(), merged.position_id), &merged)?;
+
+    let merged_ids: Vec<String> = position_ids.iter().map(|id| id.to_string()).collect();
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "merge_positions"),
+        attr("pool_id", pool_id.to_string()),
+        attr("position_id", merged.position_id.to_string()),
+        attr("merged_position_ids", merged_ids.join(",")),
+        attr("address", info.sender.clone()),
+        attr("amount", total),
+        attr("lock_type", lock_seconds.to_string()),
+        attr("settled_reward", settled),
+    ]))
+}
+///////////////////////////////////////////////////////// this func is called when user splits a staking record //////////////////////////////////
+///
+///         New records are created with the given amounts, the remaining amount stays in the record.
+///         The new records keep the lock of the record, the settled reward stays in the record.
+///         The reward base is split with the amounts.
+///         input params: position id
+///                       amounts of the new records
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn execute_split_position(
+    deps: DepsMut,
+    info: MessageInfo,
+    position_id: u64,
+    amounts: Vec<Uint128>,
+) -> Result<Response, ContractError> {
+    check_enabled(&deps, &info)?;
+    let mut position = load_position(deps.storage, position_id)?;
+    if position.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    load_enabled_pool(deps.storage, position.pool_id)?;
+
+    let cfg = CONFIG.load(deps.storage)?;
+    if amounts.is_empty()
+        || amounts
+            .iter()
+            .any(|amount| amount.is_zero() || *amount < cfg.min_stake_amount)
+    {
+        return Err(ContractError::InvalidInput {});
+    }
+    check_position_limit(
+        deps.storage,
+        &cfg,
+        position.pool_id,
+        &position.owner,
+        position.received,
+        amounts.len() as u64,
+    )?;
+    let split_total = amounts.iter().try_fold(Uint128::zero(), |total, amount| {
+        total.checked_add(*amount).map_err(StdError::from)
+    })?;
+    let remaining = position
+        .amount
+        .checked_sub(split_total)
+        .map_err(|_| ContractError::NotEnoughStake {})?;
+    if remaining.is_zero() || remaining < cfg.min_stake_amount {
+        return Err(ContractError::InvalidInput {});
+    }
+
+    let mut remaining_base = position.reward_base;
+    let mut new_ids: Vec<String> = vec![];
+    for amount in amounts {
+        let reward_base = checked_ratio(position.reward_base, amount.u128(), position.amount.u128())?;
+        remaining_base = remaining_base.checked_sub(reward_base).map_err(StdError::from)?;
+        let new_id = create_position(
+            deps.storage,
+            Position {
+                amount,
+                reward_base,
+                reward: Uint128::zero(),
+                accrued: Uint128::zero(),
+                ..position.clone()
+            },
+        )?;
+        new_ids.push(new_id.to_string());
+    }
+
+    position.amount = remaining;
+    position.reward_base = remaining_base;
+    positions().save(deps.storage, (position.owner.clone(), position_id), &position)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "split_position"),
+        attr("pool_id", position.pool_id.to_string()),
+        attr("position_id", position_id.to_string()),
+        attr("new_position_ids", new_ids.join(",")),
+        attr("address", info.sender.clone()),
+        attr("remaining_amount", remaining),
+    ]))
+}
+///////////////////////////////////////////////////////// this func is called for saving a new staking record //////////////////////////////////
+///
+///         Every staking record gets a new position id, which is never reused.
+///         The position id is the token id of the cw721 token of the record as well.
+///         input params: staking record
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+fn create_position(storage: &mut dyn Storage, mut position: Position) -> StdResult<u64> {
+    let position_id = POSITION_COUNT.load(storage)?;
+    position.position_id = position_id;
+
+    positions().save(storage, (position.owner.clone(), position_id), &position)?;
+    POSITION_COUNT.save(storage, &(position_id + 1))?;
+    NUM_TOKENS.update(storage, |count| -> StdResult<_> { Ok(count + 1) })?;
+
+    Ok(position_id)
+}
+
+fn remove_position(storage: &mut dyn Storage, position: &Position) -> StdResult<()> {
+    positions().remove(storage, (position.owner.clone(), position.position_id))?;
+    POSITION_APPROVALS.remove(storage, position.position_id);
+    NUM_TOKENS.update(storage, |count| -> StdResult<_> {
+        count
+            .checked_sub(1)
+            .ok_or_else(|| StdError::generic_err("num_tokens underflow"))
+    })?;
+    Ok(())
+}
+
+fn save_positions(storage: &mut dyn Storage, list: &[Position]) -> StdResult<()> {
+    for position in list {
+        positions().save(storage, (position.owner.clone(), position.position_id), position)?;
+    }
+    Ok(())
+}
+///////////////////////////////////////////////////////// this func is called for getting the staking records of a staker in the pool //////////////////////////////////
+///
+///         The records are ordered by the position id.
+///         input params: pool id
+///                       wallet address
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn load_positions(
+    storage: &dyn Storage,
+    pool_id: u64,
+    owner: &Addr,
+) -> StdResult<Vec<Position>> {
+    positions()
+        .idx
+        .pool_owner
+        .prefix((pool_id, owner.clone()))
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, position)| position))
+        .collect()
+}
+
+///////////////////////////////////////////////////////// this func is called for getting the staking records handled by one claim or unstake //////////////////////////////////
+///
+///         At most max_positions_per_address records are returned, the oldest first,
+///         so the records received from others can not make claim and unstake run out of gas.
+///         The flag is true if more records are left for the next call.
+///         input params: pool id
+///                       wallet address
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+fn load_positions_page(
+    storage: &dyn Storage,
+    cfg: &Config,
+    pool_id: u64,
+    owner: &Addr,
+) -> StdResult<(Vec<Position>, bool)> {
+    let limit = cfg.max_positions_per_address as usize;
+    let mut list = positions()
+        .idx
+        .pool_owner
+        .prefix((pool_id, owner.clone()))
+        .range(storage, None, None, Order::Ascending)
+        .take(limit + 1)
+        .map(|item| item.map(|(_, position)| position))
+        .collect::<StdResult<Vec<Position>>>()?;
+    let more = list.len() > limit;
+    list.truncate(limit);
+    Ok((list, more))
+}
+
+fn load_position(storage: &dyn Storage, position_id: u64) -> Result<Position, ContractError> {
+    positions()
+        .idx
+        .id
+        .item(storage, position_id)?
+        .map(|(_, position)| position)
+        .ok_or(ContractError::PositionNotFound {})
+}
+
+// the number of staking records staked by an address, or staked for it by others, is limited
+// the records received from others are counted apart, so they can not fill the cap of the owner's own stakes
+fn check_position_limit(
+    storage: &dyn Storage,
+    cfg: &Config,
+    pool_id: u64,
+    owner: &Addr,
+    received: bool,
+    adding: u64,
+) -> Result<(), ContractError> {
+    let mut count = 0u64;
+    for item in positions()
+        .idx
+        .pool_owner
+        .prefix((pool_id, owner.clone()))
+        .range(storage, None, None, Order::Ascending)
+    {
+        if item?.1.received == received {
+            count += 1;
+        }
+    }
+    if count + adding > cfg.max_positions_per_address {
+        return Err(ContractError::StakingRecordIndexOverflow {});
+    }
+    Ok(())
+}
+
+fn parse_token_id(token_id: &str) -> Result<u64, ContractError> {
+    token_id
+        .parse::<u64>()
+        .map_err(|_| ContractError::PositionNotFound {})
+}
+///////////////////////////////////////////////////////// this func is called for checking the permission of the nft //////////////////////////////////
+///
+///         The owner, the approved spender and the operator of the owner can send the nft.
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+fn check_can_send(
+    deps: &DepsMut,
+    env: &Env,
+    sender: &Addr,
+    position: &Position,
+) -> Result<(), ContractError> {
+    if position.owner == *sender {
+        return Ok(());
+    }
+
+    let approvals = POSITION_APPROVALS
+        .may_load(deps.storage, position.position_id)?
+        .unwrap_or_default();
+    if approvals
+        .iter()
+        .any(|approval| approval.spender == *sender && !approval.expires.is_expired(&env.block))
+    {
+        return Ok(());
+    }
+
+    match OPERATORS.may_load(deps.storage, (&position.owner, sender))? {
+        Some(expires) if !expires.is_expired(&env.block) => Ok(()),
+        _ => Err(ContractError::Unauthorized {}),
+    }
+}
+///////////////////////////////////////////////////////// this func is called for moving a staking record to another address //////////////////////////////////
+///
+///         The staking record keeps its lock and reward state, only the owner is changed.
+///         It is not limited by the position cap, as escrows of marketplaces hold many records, and it is marked as received,
+///         so it does not fill the cap of the recipient's own stakes. The approvals of the nft are cleared.
+///         input params: sender address
+///                       recipient address
+///                       position id
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+fn transfer_position(
+    deps: &mut DepsMut,
+    env: &Env,
+    sender: &Addr,
+    recipient: &Addr,
+    position_id: u64,
+) -> Result<(), ContractError> {
+    let mut position = load_position(deps.storage, position_id)?;
+    check_can_send(deps, env, sender, &position)?;
+
+    positions().remove(deps.storage, (position.owner.clone(), position_id))?;
+    position.owner = recipient.clone();
+    position.received = true;
+    positions().save(deps.storage, (recipient.clone(), position_id), &position)?;
+    POSITION_APPROVALS.remove(deps.storage, position_id);
+
+    Ok(())
+}
+///////////////////////////////////////////////////////// this func is called when the staking position nft is transferred on the marketplace //////////////////////////////////
+///
+///         The locked staking record is moved to the recipient, who can claim and unstake it from now on.
+///         input params: recipient address
+///                       nft token id
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn execute_transfer_nft(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: String,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    check_enabled(&deps, &info)?;
+    let recipient_addr = deps.api.addr_validate(&recipient)?;
+    let position_id = parse_token_id(&token_id)?;
+    transfer_position(&mut deps, &env, &info.sender, &recipient_addr, position_id)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "transfer_nft"),
+        attr("sender", info.sender.clone()),
+        attr("recipient", recipient),
+        attr("token_id", token_id),
+    ]))
+}
+///////////////////////////////////////////////////////// this func is called when the staking position nft is sent to a contract //////////////////////////////////
+///
+///         The staking record is moved to the contract and the contract is notified with the cw721 receive message.
+///         input params: contract address
+///                       nft token id
+///                       message for the contract
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn execute_send_nft(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    contract: String,
+    token_id: String,
+    msg: Binary,
+) -> Result<Response, ContractError> {
+    check_enabled(&deps, &info)?;
+    let contract_addr = deps.api.addr_validate(&contract)?;
+    let position_id = parse_token_id(&token_id)?;
+    transfer_position(&mut deps, &env, &info.sender, &contract_addr, position_id)?;
+
+    let send = Cw721ReceiveMsg {
+        sender: info.sender.to_string(),
+        token_id: token_id.clone(),
+        msg,
+    };
+
+    Ok(Response::new()
+        .add_message(send.into_cosmos_msg(contract.clone())?)
+        .add_attributes(vec![
+            attr("action", "send_nft"),
+            attr("sender", info.sender.clone()),
+            attr("recipient", contract),
+            attr("token_id", token_id),
+        ]))
+}
+///////////////////////////////////////////////////////// this func is called for approving a spender of the staking position nft //////////////////////////////////
+///
+///         The owner or the operator of the owner can approve a spender, for example the marketplace contract.
+///         input params: spender address
+///                       nft token id
+///                       expiration(optional, never by default)
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn execute_approve(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    spender: String,
+    token_id: String,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    let spender_addr = deps.api.addr_validate(&spender)?;
+    let position = load_position(deps.storage, parse_token_id(&token_id)?)?;
+    check_can_approve(&deps, &env, &info.sender, &position)?;
+
+    let expires = expires.unwrap_or_default();
+    if expires.is_expired(&env.block) {
+        return Err(ContractError::Expired {});
+    }
+
+    let mut approvals = POSITION_APPROVALS
+        .may_load(deps.storage, position.position_id)?
+        .unwrap_or_default();
+    approvals.retain(|approval| approval.spender != spender_addr);
+    approvals.push(Approval {
+        spender: spender_addr.to_string(),
+        expires,
+    });
+    POSITION_APPROVALS.save(deps.storage, position.position_id, &approvals)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "approve"),
+        attr("sender", info.sender.clone()),
+        attr("spender", spender),
+        attr("token_id", token_id),
+    ]))
+}
+///////////////////////////////////////////////////////// this func is called for revoking a spender of the staking position nft //////////////////////////////////
+///
+///         input params: spender address
+///                       nft token id
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn execute_revoke(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    spender: String,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let position = load_position(deps.storage, parse_token_id(&token_id)?)?;
+    check_can_approve(&deps, &env, &info.sender, &position)?;
+
+    let mut approvals = POSITION_APPROVALS
+        .may_load(deps.storage, position.position_id)?
+        .unwrap_or_default();
+    approvals.retain(|approval| approval.spender != spender);
+    POSITION_APPROVALS.save(deps.storage, position.position_id, &approvals)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "revoke"),
+        attr("sender", info.sender.clone()),
+        attr("spender", spender),
+        attr("token_id", token_id),
+    ]))
+}
+
+fn check_can_approve(
+    deps: &DepsMut,
+    env: &Env,
+    sender: &Addr,
+    position: &Position,
+) -> Result<(), ContractError> {
+    if position.owner == *sender {
+        return Ok(());
+    }
+    match OPERATORS.may_load(deps.storage, (&position.owner, sender))? {
+        Some(expires) if !expires.is_expired(&env.block) => Ok(()),
+        _ => Err(ContractError::Unauthorized {}),
+    }
+}
+///////////////////////////////////////////////////////// this func is called for approving an operator of all staking position nfts //////////////////////////////////
+///
+///         The operator can send and approve all staking position nfts of the sender.
+///         input params: operator address
+///                       expiration(optional, never by default)
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn execute_approve_all(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    operator: String,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    let operator_addr = deps.api.addr_validate(&operator)?;
+    let expires = expires.unwrap_or_default();
+    if expires.is_expired(&env.block) {
+        return Err(ContractError::Expired {});
+    }
+
+    OPERATORS.save(deps.storage, (&info.sender, &operator_addr), &expires)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "approve_all"),
+        attr("sender", info.sender.clone()),
+        attr("operator", operator),
+    ]))
+}
+///////////////////////////////////////////////////////// this func is called for revoking an operator of all staking position nfts //////////////////////////////////
+///
+///         input params: operator address
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn execute_revoke_all(
+    deps: DepsMut,
+    info: MessageInfo,
+    operator: String,
+) -> Result<Response, ContractError> {
+    let operator_addr = deps.api.addr_validate(&operator)?;
+    OPERATORS.remove(deps.storage, (&info.sender, &operator_addr));
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "revoke_all"),
+        attr("sender", info.sender.clone()),
+        attr("operator", operator),
+    ]))
+}
+
+///////////////////////////////////////////////////////// this func is called for checking ownership//////////////////////////////////
+///
+///         Owner is set when contract is instantiated.
+///         Using this function, we can authorize the ownership
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn check_owner(deps: &DepsMut, info: &MessageInfo) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+
+    if info.sender != cfg.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(Response::new().add_attribute("action", "check_owner"))
+}
+///////////////////////////////////////////////////////// this func is called for checking that admin actions can be done at once //////////////////////////////////
+///
+///         While the admin timelock is set, the owner changes, the constants, the pool tiers, the reward vesting,
+///         the validators and the withdraws have to be queued.
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+fn check_no_timelock(deps: &DepsMut) -> Result<(), ContractError> {
+    if CONFIG.load(deps.storage)?.admin_timelock_seconds > 0 {
+        return Err(ContractError::TimelockActive {});
+    }
+    Ok(())
+}
+///////////////////////////////////////////////////////// this func is called for checking enable state//////////////////////////////////
+///
+///         Enable state is set when contract is instantiated.
+///         The default vale is true.
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn check_enabled(deps: &DepsMut, _info: &MessageInfo) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    if !cfg.enabled {
+        return Err(ContractError::Disabled {});
+    }
+    Ok(Response::new().add_attribute("action", "check_enabled"))
+}
+///////////////////////////////////////////////////////// this func is called for updating the ownership//////////////////////////////////
+///
+///         Owner is set when contract is instantiated.
+///         if changing ownership is needed, we can use this function.
 ///         input params: new owner(new walletaddress)
 ///     
 /// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn execute_update_owner(
+    deps: DepsMut,
+    info: MessageInfo,
+    owner: String,
+) -> Result<Response, ContractError> {
+    // authorize owner
+    check_owner(&deps, &info)?;
+    let owner = deps.api.addr_validate(&owner)?;
+
+    CONFIG.update(deps.storage, |mut exists| -> StdResult<_> {
+        exists.owner = owner.clone();
+        Ok(exists)
+    })?;
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_owner"),
+        attr("owner", owner),
+    ]))
+}
+///////////////////////////////////////////////////////// this func is called for updating the enable state //////////////////////////////////
+///
+///         If we need changing the enable state of the contract, this function is used.
+///         input params: new state(BOOL)
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn execute_update_enabled(
+    deps: DepsMut,
+    info: MessageInfo,
+    enabled: bool,
+) -> Result<Response, ContractError> {
+    // authorize owner
+    check_owner(&deps, &info)?;
+
+    CONFIG.update(deps.storage, |mut exists| -> StdResult<_> {
+        exists.enabled = enabled;
+        Ok(exists)
+    })?;
+    Ok(Response::new().add_attribute("action", "update_enabled"))
+}
+///////////////////////////////////////////////////////// this func is called for updating the emergency mode //////////////////////////////////
+///
+///         In emergency mode every user can take the staked principal out with the emergency withdraw.
+///         input params: new emergency mode(BOOL)
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn execute_update_emergency(
+    deps: DepsMut,
+    info: MessageInfo,
+    emergency: bool,
+) -> Result<Response, ContractError> {
+    // authorize owner
+    check_owner(&deps, &info)?;
+
+    CONFIG.update(deps.storage, |mut exists| -> StdResult<_> {
+        exists.emergency = emergency;
+        Ok(exists)
+    })?;
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_emergency"),
+        attr("emergency", emergency.to_string()),
+    ]))
+}
+///////////////////////////////////////////////////////// this func is called for pausing or resuming one kind of action //////////////////////////////////
+///
+///         Unlike the enable state, every flag only stops its own action, so a paused claim keeps unstaking open.
+///         Withdrawing the unbonded principal and the emergency withdraw are never paused.
+///         An event is emitted for every flag which is changed.
+///         input params: stake paused flag(optional)
+///                       claim paused flag(optional)
+///                       unstake paused flag(optional)
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn execute_update_paused(
+    deps: DepsMut,
+    info: MessageInfo,
+    stake_paused: Option<bool>,
+    claim_paused: Option<bool>,
+    unstake_paused: Option<bool>,
+) -> Result<Response, ContractError> {
+    // authorize owner
+    check_owner(&deps, &info)?;
+
+    let mut cfg = CONFIG.load(deps.storage)?;
+    let mut events = vec![];
+    for (action, flag, paused) in [
+        ("stake", &mut cfg.stake_paused, stake_paused),
+        ("claim", &mut cfg.claim_paused, claim_paused),
+        ("unstake", &mut cfg.unstake_paused, unstake_paused),
+    ] {
+        if let Some(paused) = paused {
+            if *flag != paused {
+                *flag = paused;
+                events.push(
+                    Event::new("incentive_pause")
+                        .add_attribute("paused_action", action)
+                        .add_attribute("paused", paused.to_string()),
+                );
+            }
+        }
+    }
+    CONFIG.save(deps.storage, &cfg)?;
+
+    Ok(Response::new().add_events(events).add_attributes(vec![
+        attr("action", "update_paused"),
+        attr("stake_paused", cfg.stake_paused.to_string()),
+        attr("claim_paused", cfg.claim_paused.to_string()),
+        attr("unstake_paused", cfg.unstake_paused.to_string()),
+    ]))
+}
+///////////////////////////////////////////////////////// this func is called for updating the constants //////////////////////////////////
+///
+///         If we need changing the reward interval, the reward addresses, the reward denom, the stake token
+///         or the swap of an lp pool, this function is used.
+///         Only the given values are changed. The reward interval must be within the bounds set at instantiate.
+///         The assets of the default pool are changed with the reward denom and the stake token.
+///         The stake token can only be changed while the default pool has no stake, as the stake is returned in it.
+///         input params: new reward_interval(optional)
+///                       new charity, burn and artists wallet addresses(optional)
+///                       new reward token denom(optional)
+///                       new stake token address(optional)
+///                       lp pool id and new swap address(optional)
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn execute_update_constants(
+    deps: DepsMut,
+    info: MessageInfo,
+    update: ConstantsUpdate,
+) -> Result<Response, ContractError> {
+    // authorize owner
+    check_owner(&deps, &info)?;
+
+    let mut cfg = CONFIG.load(deps.storage)?;
+    let mut attrs = vec![attr("action", "update_constants")];
+
+    if let Some(reward_interval) = update.reward_interval {
+        check_reward_interval(&cfg, reward_interval)?;
+        cfg.reward_interval = reward_interval;
+        attrs.push(attr("reward_interval", reward_interval.to_string()));
+    }
+    if let Some(address) = update.reward_charity_address {
+        cfg.reward_charity_address = deps.api.addr_validate(&address)?;
+        attrs.push(attr("reward_charity_address", address));
+    }
+    if let Some(address) = update.reward_burn_address {
+        cfg.reward_burn_address = deps.api.addr_validate(&address)?;
+        attrs.push(attr("reward_burn_address", address));
+    }
+    if let Some(address) = update.reward_artists_address {
+        cfg.reward_artists_address = deps.api.addr_validate(&address)?;
+        attrs.push(attr("reward_artists_address", address));
+    }
+    // the default pool is created with the reward denom and the stake token, so it follows them
+    let mut default_pool = load_pool(deps.storage, 0)?;
+    if let Some(denom) = update.reward_token_denom {
+        if denom.is_empty() {
+            return Err(ContractError::InvalidInput {});
+        }
+        cfg.reward_token_denom = denom.clone();
+        default_pool.native_reward_asset = Denom::Native(denom.clone());
+        attrs.push(attr("reward_token_denom", denom));
+    }
+    if let Some(address) = update.stake_token_address {
+        cfg.stake_token_address = deps.api.addr_validate(&address)?;
+        check_default_pool_unstaked(deps.storage)?;
+        default_pool.stake_asset = Denom::Cw20(cfg.stake_token_address.clone());
+        default_pool.reward_asset = Denom::Cw20(cfg.stake_token_address.clone());
+        attrs.push(attr("stake_token_address", address));
+    }
+    CONFIG.save(deps.storage, &cfg)?;
+    POOLS.save(deps.storage, 0, &default_pool)?;
+
+    // the reward base of the staked records is fixed, so only the new stakes use the new swap
+    if let Some(update) = update.swap_address {
+        let mut pool = load_pool(deps.storage, update.pool_id)?;
+        let lp = pool.lp.as_mut().ok_or(ContractError::InvalidInput {})?;
+        lp.swap_address = deps.api.addr_validate(&update.swap_address)?;
+        check_lp_swap(deps.querier, &pool)?;
+        POOLS.save(deps.storage, update.pool_id, &pool)?;
+        attrs.push(attr("pool_id", update.pool_id.to_string()));
+        attrs.push(attr("swap_address", update.swap_address));
+    }
+
+    Ok(Response::new().add_attributes(attrs))
+}
+
+// the staking records keep the asset they were staked in, so the stake asset of a pool with stake is not changed
+fn check_default_pool_unstaked(storage: &dyn Storage) -> Result<(), ContractError> {
+    if !POOL_STAKED.may_load(storage, 0)?.unwrap_or_default().is_zero() {
+        return Err(ContractError::PoolHasStake { pool_id: 0 });
+    }
+    Ok(())
+}
+
+// the reward is scaled by the interval, so it is kept within the bounds of the instantiate
+fn check_reward_interval(cfg: &Config, reward_interval: u64) -> Result<(), ContractError> {
+    if reward_interval < cfg.min_reward_interval || reward_interval > cfg.max_reward_interval {
+        return Err(ContractError::InvalidRewardInterval {
+            min: cfg.min_reward_interval,
+            max: cfg.max_reward_interval,
+        });
+    }
+    Ok(())
+}
+///////////////////////////////////////////////////////// this func is called for updating the stake limits //////////////////////////////////
+///
+///         The limits keep the number of staking records of an address small enough to be looped over
+///         when claiming and unstaking.
+///         input params: minimum amount of a staking record
+///                       maximum number of staking records of an address in a pool
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn execute_update_stake_limits(
+    deps: DepsMut,
+    info: MessageInfo,
+    min_stake_amount: Uint128,
+    max_positions_per_address: u64,
+) -> Result<Response, ContractError> {
+    // authorize owner
+    check_owner(&deps, &info)?;
 
-pub fn execute_update_owner(
+    if min_stake_amount.is_zero() || max_positions_per_address == 0 {
+        return Err(ContractError::InvalidInput {});
+    }
+
+    CONFIG.update(deps.storage, |mut exists| -> StdResult<_> {
+        exists.min_stake_amount = min_stake_amount;
+        exists.max_positions_per_address = max_positions_per_address;
+        Ok(exists)
+    })?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_stake_limits"),
+        attr("min_stake_amount", min_stake_amount),
+        attr("max_positions_per_address", max_positions_per_address.to_string()),
+    ]))
+}
+///////////////////////////////////////////////////////// this func is called for updating the contracts which can stake for another address //////////////////////////////////
+///
+///         If the allowlist is not given, nobody can stake for another address.
+///         input params: contract addresses(optional)
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn execute_update_stake_on_behalf_allowlist(
     deps: DepsMut,
     info: MessageInfo,
-    owner: Addr,
+    allowlist: Option<Vec<String>>,
 ) -> Result<Response, ContractError> {
     // authorize owner
     check_owner(&deps, &info)?;
 
+    let allowlist = match allowlist {
+        Some(allowlist) => Some(
+            allowlist
+                .iter()
+                .map(|addr| deps.api.addr_validate(addr))
+                .collect::<StdResult<Vec<Addr>>>()?,
+        ),
+        None => None,
+    };
+
     CONFIG.update(deps.storage, |mut exists| -> StdResult<_> {
-        exists.owner = owner;
+        exists.stake_on_behalf_allowlist = allowlist.clone();
         Ok(exists)
     })?;
-    Ok(Response::new().add_attribute("action", "update_owner"))
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_stake_on_behalf_allowlist"),
+        attr(
+            "allowlist",
+            allowlist.map_or("none".to_string(), |allowlist| {
+                allowlist
+                    .iter()
+                    .map(|addr| addr.to_string())
+                    .collect::<Vec<String>>()
+                    .join(",")
+            }),
+        ),
+    ]))
 }
-///////////////////////////////////////////////////////// this func is called for updating the enable state //////////////////////////////////
+///////////////////////////////////////////////////////// this func is called for updating the vesting period of the claimed reward //////////////////////////////////
 ///
-///         If we need changing the enable state of the contract, this function is used.
-///         input params: new state(BOOL)
+///         If the vesting period is 0, the reward is sent to the staker directly when claiming.
+///         The vesting period is at most MAX_REWARD_VESTING_SECONDS.
+///         input params: vesting period in seconds
 ///     
-/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
-
-pub fn execute_update_enabled(
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn execute_update_reward_vesting(
     deps: DepsMut,
     info: MessageInfo,
-    enabled: bool,
+    vesting_seconds: u64,
 ) -> Result<Response, ContractError> {
     // authorize owner
     check_owner(&deps, &info)?;
+    check_reward_vesting(vesting_seconds)?;
 
     CONFIG.update(deps.storage, |mut exists| -> StdResult<_> {
-        exists.enabled = enabled;
+        exists.reward_vesting_seconds = vesting_seconds;
         Ok(exists)
     })?;
-    Ok(Response::new().add_attribute("action", "update_enabled"))
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_reward_vesting"),
+        attr("vesting_seconds", vesting_seconds.to_string()),
+    ]))
 }
-///////////////////////////////////////////////////////// this func is called for updating reward interval //////////////////////////////////
+///////////////////////////////////////////////////////// this func is called for creating a new staking pool //////////////////////////////////
 ///
-///         If we need changing reward interval, this function is used.
-///         input params: new reward_interval(u64)
+///         If we need a new staking campaign, this function is used instead of deploying a new contract.
+///         The id of the new pool is returned in the pool_id attribute.
+///         input params: stake asset
+///                       reward asset(sent to the staker directly)
+///                       native reward asset(split by the reward distribution)
+///                       tiers(the index of the tier is the lock_type of the stake message)
+///                       lp mode(optional, the stake asset is the lp token of the wasmswap pool)
+///                       delegated(the native stake asset is delegated to the validators)
 ///     
 /// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn execute_create_pool(
+    deps: DepsMut,
+    info: MessageInfo,
+    pool: Pool,
+) -> Result<Response, ContractError> {
+    // authorize owner
+    check_owner(&deps, &info)?;
 
-pub fn execute_update_constants(
+    check_tiers(&pool.tiers)?;
+    check_lp_swap(deps.querier, &pool)?;
+    if pool.delegated {
+        // the staking reward is paid in the bonded denom with the juno reward
+        let bonded = Denom::Native(deps.querier.query_bonded_denom()?);
+        if pool.stake_asset != bonded || pool.native_reward_asset != bonded || pool.lp.is_some() {
+            return Err(ContractError::UnacceptableToken {});
+        }
+    }
+
+    let pool_id = POOL_COUNT.load(deps.storage)?;
+    POOLS.save(deps.storage, pool_id, &pool)?;
+    POOL_COUNT.save(deps.storage, &(pool_id + 1))?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "create_pool"),
+        attr("pool_id", pool_id.to_string()),
+    ]))
+}
+// the staked amount of a tier is keyed by its lock period and the lock type of a stake is the index of the tier,
+// so the lock periods must be unique and increasing, and a record without a lock would get the reward on every claim
+fn check_tiers(tiers: &[Tier]) -> Result<(), ContractError> {
+    if tiers.is_empty() {
+        return Err(ContractError::InvalidInput {});
+    }
+    let mut last_lock_seconds = 0u64;
+    for tier in tiers {
+        if tier.lock_seconds <= last_lock_seconds
+            || tier.lock_seconds > constants::MAX_LOCK_SECONDS
+        {
+            return Err(ContractError::InvalidInput {});
+        }
+        last_lock_seconds = tier.lock_seconds;
+    }
+    Ok(())
+}
+// the stake asset of lp pools must be the lp token of the swap, and the underlying reward asset one of the pair
+fn check_lp_swap(querier: QuerierWrapper, pool: &Pool) -> Result<(), ContractError> {
+    if let Some(lp) = &pool.lp {
+        let swap_info = util::get_swap_info(querier, lp.swap_address.clone())?;
+        if pool.stake_asset != Denom::Cw20(Addr::unchecked(swap_info.lp_token_address)) {
+            return Err(ContractError::UnacceptableToken {});
+        }
+        if lp.reward_on_underlying
+            && swap_info.token1_denom != pool.reward_asset
+            && swap_info.token2_denom != pool.reward_asset
+        {
+            return Err(ContractError::UnacceptableToken {});
+        }
+    }
+    Ok(())
+}
+///////////////////////////////////////////////////////// this func is called for validating the asset of a pool message //////////////////////////////////
+///
+///         input params: native denom or cw20 token address
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+fn validate_denom(api: &dyn Api, denom: UncheckedDenom) -> StdResult<Denom> {
+    match denom {
+        UncheckedDenom::Native(denom) => Ok(Denom::Native(denom)),
+        UncheckedDenom::Cw20(address) => Ok(Denom::Cw20(api.addr_validate(&address)?)),
+    }
+}
+
+fn validate_lp_pool(api: &dyn Api, lp: UncheckedLpPool) -> StdResult<LpPool> {
+    Ok(LpPool {
+        swap_address: api.addr_validate(&lp.swap_address)?,
+        reward_on_underlying: lp.reward_on_underlying,
+    })
+}
+///////////////////////////////////////////////////////// this func is called for updating a staking pool //////////////////////////////////
+///
+///         If we need changing the tiers or the enable state of a pool, this function is used.
+///         The existing staking records of the pool use the new tiers from now on,
+///         so a tier can only be removed when nothing is staked in it.
+///         The lock periods of the tiers must be increasing and not longer than the max lock period.
+///         A disabled pool takes no new stakes, but its staking records can still be unstaked.
+///         input params: pool id
+///                       new tiers(optional)
+///                       new enable state(optional)
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn execute_update_pool(
+    deps: DepsMut,
+    info: MessageInfo,
+    pool_id: u64,
+    tiers: Option<Vec<Tier>>,
+    enabled: Option<bool>,
+) -> Result<Response, ContractError> {
+    // authorize owner
+    check_owner(&deps, &info)?;
+
+    let mut pool = load_pool(deps.storage, pool_id)?;
+    if let Some(tiers) = tiers {
+        check_tiers(&tiers)?;
+        // a staked tier must be kept, or its positions would get no reward
+        let staked = TIER_STAKED
+            .prefix(pool_id)
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for (lock_seconds, amount) in staked {
+            if !amount.is_zero() && !tiers.iter().any(|tier| tier.lock_seconds == lock_seconds) {
+                return Err(ContractError::TierInUse { lock_seconds });
+            }
+        }
+        pool.tiers = tiers;
+    }
+    if let Some(enabled) = enabled {
+        pool.enabled = enabled;
+    }
+    POOLS.save(deps.storage, pool_id, &pool)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_pool"),
+        attr("pool_id", pool_id.to_string()),
+        attr("enabled", pool.enabled.to_string()),
+    ]))
+}
+///////////////////////////////////////////////////////// this func is called for updating the staking cap of a pool //////////////////////////////////
+///
+///         The stake over the cap is refunded. The capacity of each tier is set with the tiers of the pool.
+///         input params: pool id
+///                       maximum total staked amount of the pool(optional, no cap if not given)
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn execute_update_pool_cap(
+    deps: DepsMut,
+    info: MessageInfo,
+    pool_id: u64,
+    max_total_staked: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    // authorize owner
+    check_owner(&deps, &info)?;
+
+    let mut pool = load_pool(deps.storage, pool_id)?;
+    pool.max_total_staked = max_total_staked;
+    POOLS.save(deps.storage, pool_id, &pool)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_pool_cap"),
+        attr("pool_id", pool_id.to_string()),
+        attr(
+            "max_total_staked",
+            max_total_staked.map_or("none".to_string(), |max| max.to_string()),
+        ),
+    ]))
+}
+///////////////////////////////////////////////////////// this func is called for updating the validators //////////////////////////////////
+///
+///         The native stake of delegated pools is delegated to these validators from now on.
+///         The existing delegations are kept until they are undelegated.
+///         input params: validator addresses
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn execute_update_validators(
     deps: DepsMut,
     info: MessageInfo,
-    reward_interval: u64,
+    validators: Vec<String>,
 ) -> Result<Response, ContractError> {
     // authorize owner
     check_owner(&deps, &info)?;
+    check_validators(deps.querier, &validators)?;
 
     CONFIG.update(deps.storage, |mut exists| -> StdResult<_> {
-        exists.reward_interval = reward_interval;
+        exists.validators = validators;
         Ok(exists)
     })?;
 
-    Ok(Response::new().add_attribute("action", "update_constants"))
+    Ok(Response::new().add_attribute("action", "update_validators"))
+}
+
+fn check_validators(querier: QuerierWrapper, validators: &[String]) -> Result<(), ContractError> {
+    for validator in validators.iter() {
+        if querier.query_validator(validator.clone())?.is_none() {
+            return Err(ContractError::InvalidInput {});
+        }
+    }
+    Ok(())
+}
+
+fn check_reward_vesting(vesting_seconds: u64) -> Result<(), ContractError> {
+    if vesting_seconds > constants::MAX_REWARD_VESTING_SECONDS {
+        return Err(ContractError::InvalidInput {});
+    }
+    Ok(())
+}
+///////////////////////////////////////////////////////// this func is called for queueing an admin action //////////////////////////////////
+///
+///         The action can be executed once the admin timelock at the time of queueing has passed,
+///         so the stakers can see it with the pending admin actions query and exit before it lands.
+///         The values of the action are checked when it is queued.
+///         Only owner can call this function
+///         input params: admin action
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn execute_queue_admin_action(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    action: AdminAction,
+) -> Result<Response, ContractError> {
+    check_owner(&deps, &info)?;
+
+    let cfg = CONFIG.load(deps.storage)?;
+    check_admin_action(deps.as_ref(), &cfg, &action)?;
+
+    let id = ADMIN_ACTION_COUNT.may_load(deps.storage)?.unwrap_or_default();
+    ADMIN_ACTION_COUNT.save(deps.storage, &(id + 1))?;
+
+    let queued_at = env.block.time.seconds();
+    let pending = PendingAdminAction {
+        id,
+        action,
+        queued_at,
+        executable_at: queued_at + cfg.admin_timelock_seconds,
+    };
+    ADMIN_ACTIONS.save(deps.storage, id, &pending)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "queue_admin_action"),
+        attr("id", id.to_string()),
+        attr("executable_at", pending.executable_at.to_string()),
+    ]))
+}
+
+// the queued values are checked now, so an action which would fail is not found only after the timelock
+fn check_admin_action(deps: Deps, cfg: &Config, action: &AdminAction) -> Result<(), ContractError> {
+    match action {
+        AdminAction::UpdateOwner { owner } => {
+            deps.api.addr_validate(owner)?;
+        }
+        AdminAction::UpdateConstants(update) => {
+            if let Some(reward_interval) = update.reward_interval {
+                check_reward_interval(cfg, reward_interval)?;
+            }
+            for address in [
+                update.reward_charity_address.as_ref(),
+                update.reward_burn_address.as_ref(),
+                update.reward_artists_address.as_ref(),
+                update.stake_token_address.as_ref(),
+            ]
+            .iter()
+            .flatten()
+            {
+                deps.api.addr_validate(address)?;
+            }
+            if update.reward_token_denom.as_ref().is_some_and(|denom| denom.is_empty()) {
+                return Err(ContractError::InvalidInput {});
+            }
+            if update.stake_token_address.is_some() {
+                check_default_pool_unstaked(deps.storage)?;
+            }
+            if let Some(update) = &update.swap_address {
+                if load_pool(deps.storage, update.pool_id)?.lp.is_none() {
+                    return Err(ContractError::InvalidInput {});
+                }
+                deps.api.addr_validate(&update.swap_address)?;
+            }
+        }
+        AdminAction::UpdateAdminTimelock { timelock_seconds } => {
+            if *timelock_seconds > constants::MAX_ADMIN_TIMELOCK_SECONDS {
+                return Err(ContractError::InvalidInput {});
+            }
+        }
+        AdminAction::UpdatePoolTiers { pool_id, tiers } => {
+            load_pool(deps.storage, *pool_id)?;
+            check_tiers(tiers)?;
+        }
+        AdminAction::UpdateRewardVesting { vesting_seconds } => {
+            check_reward_vesting(*vesting_seconds)?;
+        }
+        AdminAction::UpdateValidators { validators } => {
+            check_validators(deps.querier, validators)?;
+        }
+        AdminAction::WithdrawReward { .. } | AdminAction::WithdrawStake { .. } => {}
+    }
+    Ok(())
+}
+///////////////////////////////////////////////////////// this func is called for executing a queued admin action //////////////////////////////////
+///
+///         Only owner can call this function after the timelock of the action has passed.
+///         input params: id of the queued action
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn execute_admin_action(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    check_owner(&deps, &info)?;
+
+    let pending = ADMIN_ACTIONS
+        .may_load(deps.storage, id)?
+        .ok_or(ContractError::AdminActionNotFound {})?;
+    if env.block.time.seconds() < pending.executable_at {
+        return Err(ContractError::TimelockNotExpired {
+            executable_at: pending.executable_at,
+        });
+    }
+    ADMIN_ACTIONS.remove(deps.storage, id);
+
+    let res = match pending.action {
+        AdminAction::UpdateOwner { owner } => execute_update_owner(deps, info, owner),
+        AdminAction::UpdateConstants(update) => execute_update_constants(deps, info, update),
+        AdminAction::WithdrawReward { amount } => execute_withdraw_reward(deps, env, info, amount),
+        AdminAction::WithdrawStake { amount } => execute_withdraw_stake(deps, env, info, amount),
+        AdminAction::UpdateAdminTimelock { timelock_seconds } => {
+            CONFIG.update(deps.storage, |mut exists| -> StdResult<_> {
+                exists.admin_timelock_seconds = timelock_seconds;
+                Ok(exists)
+            })?;
+            Ok(Response::new().add_attributes(vec![
+                attr("action", "update_admin_timelock"),
+                attr("timelock_seconds", timelock_seconds.to_string()),
+            ]))
+        }
+        AdminAction::UpdatePoolTiers { pool_id, tiers } => {
+            execute_update_pool(deps, info, pool_id, Some(tiers), None)
+        }
+        AdminAction::UpdateRewardVesting { vesting_seconds } => {
+            execute_update_reward_vesting(deps, info, vesting_seconds)
+        }
+        AdminAction::UpdateValidators { validators } => {
+            execute_update_validators(deps, info, validators)
+        }
+    }?;
+
+    Ok(res.add_attributes(vec![
+        attr("admin_action", "execute_admin_action"),
+        attr("id", id.to_string()),
+    ]))
+}
+///////////////////////////////////////////////////////// this func is called for cancelling a queued admin action //////////////////////////////////
+///
+///         Only owner can call this function
+///         input params: id of the queued action
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn execute_cancel_admin_action(
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    check_owner(&deps, &info)?;
+
+    if !ADMIN_ACTIONS.has(deps.storage, id) {
+        return Err(ContractError::AdminActionNotFound {});
+    }
+    ADMIN_ACTIONS.remove(deps.storage, id);
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "cancel_admin_action"),
+        attr("id", id.to_string()),
+    ]))
 }
 ///////////////////////////////////////////////////////// this func is called for withdrawing reward //////////////////////////////////
 ///
@@ -477,85 +3117,430 @@ pub fn execute_update_constants(
 ///         input pararms: the reward token amount of withdrawing
 ///     
 /// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn execute_withdraw_reward(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    check_owner(&deps, &info)?;
+
+    let cfg = CONFIG.load(deps.storage)?;
+    let asset = Denom::Native(cfg.reward_token_denom.clone());
+
+    // the stake and the reward kept for the stakers can not be withdrawn
+    let tot = available_reward(deps.storage, deps.querier, &env, &asset, Uint128::zero())?;
+
+    if tot < amount {
+        return Err(ContractError::NotEnoughReward {});
+    }
+
+    let msg = util::transfer_token_message(
+        Denom::Native(cfg.reward_token_denom.clone()),
+        amount,
+        info.sender.clone(),
+    )?;
+
+    Ok(Response::new().add_message(msg).add_attributes(vec![
+        attr("action", "withdraw_reward"),
+        attr("address", info.sender.clone()),
+        attr("amount", amount),
+    ]))
+}
+///////////////////////////////////////////////////////// this func is called for withdrawing the staked token //////////////////////////////////
+///
+///         Only owner can call this function
+///         input pararms: the withdraw amount
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn execute_withdraw_stake(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    check_owner(&deps, &info)?;
+
+    let cfg = CONFIG.load(deps.storage)?;
+
+    let tot = util::get_token_amount(
+        deps.querier,
+        Denom::Cw20(cfg.stake_token_address.clone()),
+        env.contract.address.clone(),
+    )?;
+
+    if tot < amount {
+        return Err(ContractError::NotEnoughStake {});
+    }
+
+    let msg = util::transfer_token_message(
+        Denom::Cw20(cfg.stake_token_address.clone()),
+        amount,
+        info.sender.clone(),
+    )?;
+
+    Ok(Response::new().add_message(msg).add_attributes(vec![
+        attr("action", "withdraw_stake"),
+        attr("address", info.sender.clone()),
+        attr("amount", amount),
+    ]))
+}
+
+///////////////////////////////////////////////////////// this func is called for registering a new airdrop stage //////////////////////////////////
+///
+///         The airdrop is claimed as a locked staking record of the pool, so the owner deposits
+///         the total amount of the stage in the native stake asset of the pool with this message.
+///         The airdrop of cw20 pools is deposited with the RegisterMerkleRoot message of the cw20 send instead.
+///         input params: merkle root(hex encoded sha256 of the address and amount leaves)
+///                       start(optional)
+///                       expiration(optional)
+///                       pool id
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn execute_register_merkle_root(
+    deps: DepsMut,
+    info: MessageInfo,
+    merkle_root: String,
+    start: Option<Scheduled>,
+    expiration: Option<Expiration>,
+    pool_id: u64,
+) -> Result<Response, ContractError> {
+    // authorize owner
+    check_owner(&deps, &info)?;
+
+    let coin = one_coin(&info)?;
+    register_merkle_root(
+        deps,
+        merkle_root,
+        start,
+        expiration,
+        pool_id,
+        Denom::Native(coin.denom),
+        coin.amount,
+    )
+}
+///////////////////////////////////////////////////////// this func is called for saving a new airdrop stage with its deposit //////////////////////////////////
+///
+///         The deposit must be the stake asset of the pool, and it is the total amount of the stage.
+///         input params: merkle root(hex encoded sha256 of the address and amount leaves)
+///                       start(optional)
+///                       expiration(optional)
+///                       pool id
+///                       deposited asset and amount
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+fn register_merkle_root(
+    deps: DepsMut,
+    merkle_root: String,
+    start: Option<Scheduled>,
+    expiration: Option<Expiration>,
+    pool_id: u64,
+    asset: Denom,
+    total_amount: Uint128,
+) -> Result<Response, ContractError> {
+    // check merkle root length
+    let mut root_buf: [u8; 32] = [0; 32];
+    hex::decode_to_slice(&merkle_root, &mut root_buf)?;
+    let pool = load_pool(deps.storage, pool_id)?;
+    if pool.stake_asset != asset {
+        return Err(ContractError::UnacceptableToken {});
+    }
+    if total_amount.is_zero() {
+        return Err(ContractError::InvalidInput {});
+    }
+
+    let stage = LATEST_STAGE
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        .checked_add(1)
+        .ok_or(ContractError::TooManyStages {})?;
+    AIRDROP_STAGES.save(
+        deps.storage,
+        stage,
+        &AirdropStage {
+            merkle_root: merkle_root.clone(),
+            start,
+            expiration,
+            pool_id,
+            asset,
+            total_amount,
+            claimed_amount: Uint128::zero(),
+        },
+    )?;
+    LATEST_STAGE.save(deps.storage, &stage)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "register_merkle_root"),
+        attr("stage", stage.to_string()),
+        attr("merkle_root", merkle_root),
+        attr("pool_id", pool_id.to_string()),
+        attr("total_amount", total_amount),
+    ]))
+}
+///////////////////////////////////////////////////////// this func is called when user claims the airdrop //////////////////////////////////
+///
+///         The claimed amount is staked directly as a locked staking record of the pool of the stage.
+///         It is paid from the deposit of the stage, so it is never refunded and must fit in the capacity.
+///         The stake pause and the stake limits do not apply, as the amounts are fixed by the merkle root.
+///         input params: stage
+///                       claimed amount
+///                       merkle proof(hex encoded)
+///                       lock_type (index of the tier of the pool)
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn execute_claim_airdrop(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    stage: u8,
+    amount: Uint128,
+    proof: Vec<String>,
+    lock_type: u64,
+) -> Result<Response, ContractError> {
+    check_enabled(&deps, &info)?;
+
+    let mut airdrop = AIRDROP_STAGES
+        .may_load(deps.storage, stage)?
+        .ok_or(ContractError::InvalidInput {})?;
+
+    if let Some(start) = airdrop.start {
+        if !start.is_triggered(&env.block) {
+            return Err(ContractError::StageNotBegun { stage, start });
+        }
+    }
+    if let Some(expiration) = airdrop.expiration {
+        if expiration.is_expired(&env.block) {
+            return Err(ContractError::StageExpired { stage, expiration });
+        }
+    }
 
-pub fn execute_withdraw_reward(
-    deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-    amount: Uint128,
-) -> Result<Response, ContractError> {
-    check_owner(&deps, &info)?;
+    // verify not claimed
+    if AIRDROP_CLAIMED
+        .may_load(deps.storage, (&info.sender, stage))?
+        .unwrap_or(false)
+    {
+        return Err(ContractError::Claimed {});
+    }
 
-    let mut cfg = CONFIG.load(deps.storage)?;
+    verify_merkle_proof(&airdrop.merkle_root, &info.sender, amount, proof)?;
 
-    let tot = util::get_token_amount(
-        deps.querier,
-        Denom::Native(cfg.reward_token_denom.clone()),
-        env.contract.address.clone(),
-    )?;
+    let pool = load_enabled_pool(deps.storage, airdrop.pool_id)?;
+    if pool.stake_asset != airdrop.asset {
+        return Err(ContractError::UnacceptableToken {});
+    }
+    let tier = select_tier(&pool, lock_type)?;
+    let lock_seconds = tier.lock_seconds;
+    if amount.is_zero() {
+        return Err(ContractError::InvalidInput {});
+    }
+    // the deposit of the stage can not be refunded, so the whole amount must fit in the capacity
+    if let Some(remaining) = remaining_capacity(deps.storage, airdrop.pool_id, &pool, tier)? {
+        if remaining < amount {
+            return Err(ContractError::CapacityExceeded {});
+        }
+    }
 
-    if tot < amount {
-        return Err(ContractError::NotEnoughReward {});
+    airdrop.claimed_amount = airdrop
+        .claimed_amount
+        .checked_add(amount)
+        .map_err(StdError::from)?;
+    if airdrop.claimed_amount > airdrop.total_amount {
+        return Err(ContractError::StageExhausted { stage });
     }
+    AIRDROP_STAGES.save(deps.storage, stage, &airdrop)?;
+    AIRDROP_CLAIMED.save(deps.storage, (&info.sender, stage), &true)?;
 
-    let msg = util::transfer_token_message(
-        Denom::Native(cfg.reward_token_denom.clone()),
-        amount,
+    let (position_id, msgs) = open_position(
+        deps,
+        &env,
+        airdrop.pool_id,
+        &pool,
         info.sender.clone(),
+        amount,
+        lock_seconds,
+        false,
     )?;
 
-    return Ok(Response::new().add_message(msg).add_attributes(vec![
-        attr("action", "withdraw_reward"),
+    Ok(Response::new().add_messages(msgs).add_attributes(vec![
+        attr("action", "claim_airdrop"),
+        attr("stage", stage.to_string()),
+        attr("pool_id", airdrop.pool_id.to_string()),
+        attr("position_id", position_id.to_string()),
         attr("address", info.sender.clone()),
-        attr("amount", amount),
-    ]));
+        attr("claimed_amount", amount),
+        attr("lock_type", lock_seconds.to_string()),
+    ]))
 }
-///////////////////////////////////////////////////////// this func is called for withdrawing the staked token //////////////////////////////////
+///////////////////////////////////////////////////////// this func is called when the owner takes back the unclaimed airdrop //////////////////////////////////
 ///
-///         Only owner can call this function
-///         input pararms: the withdraw amount
+///         The deposit which is not claimed until the expiration of the stage is returned to the owner.
+///         A stage without expiration can always be claimed, so its deposit is never returned.
+///         input params: stage
 ///     
-/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
-pub fn execute_withdraw_stake(
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+pub fn execute_withdraw_airdrop_remainder(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    amount: Uint128,
+    stage: u8,
 ) -> Result<Response, ContractError> {
     check_owner(&deps, &info)?;
 
-    let mut cfg = CONFIG.load(deps.storage)?;
-
-    let tot = util::get_token_amount(
-        deps.querier,
-        Denom::Cw20(cfg.stake_token_address.clone()),
-        env.contract.address.clone(),
-    )?;
+    let mut airdrop = AIRDROP_STAGES
+        .may_load(deps.storage, stage)?
+        .ok_or(ContractError::InvalidInput {})?;
+    match airdrop.expiration {
+        Some(expiration) if expiration.is_expired(&env.block) => {}
+        _ => return Err(ContractError::StageNotExpired { stage }),
+    }
 
-    if tot < amount {
-        return Err(ContractError::NotEnoughStake {});
+    let remainder = airdrop.total_amount - airdrop.claimed_amount;
+    if remainder.is_zero() {
+        return Err(ContractError::StageExhausted { stage });
     }
+    // the stage is closed at the claimed amount, so the remainder is withdrawn once
+    airdrop.total_amount = airdrop.claimed_amount;
+    AIRDROP_STAGES.save(deps.storage, stage, &airdrop)?;
 
-    let msg = util::transfer_token_message(
-        Denom::Cw20(cfg.stake_token_address.clone()),
-        amount,
-        info.sender.clone(),
-    )?;
+    Ok(Response::new()
+        .add_message(util::transfer_token_message(
+            airdrop.asset.clone(),
+            remainder,
+            info.sender.clone(),
+        )?)
+        .add_attributes(vec![
+            attr("action", "withdraw_airdrop_remainder"),
+            attr("stage", stage.to_string()),
+            attr("asset", util::denom_key(&airdrop.asset)),
+            attr("amount", remainder),
+        ]))
+}
 
-    return Ok(Response::new().add_message(msg).add_attributes(vec![
-        attr("action", "withdraw_stake"),
-        attr("address", info.sender.clone()),
-        attr("amount", amount),
-    ]));
+fn verify_merkle_proof(
+    merkle_root: &str,
+    address: &Addr,
+    amount: Uint128,
+    proof: Vec<String>,
+) -> Result<(), ContractError> {
+    let user_input = format!("{}{}", address, amount);
+    let hash: [u8; 32] = sha2::Sha256::digest(user_input.as_bytes()).into();
+
+    let hash = proof.into_iter().try_fold(hash, |hash, p| {
+        let mut proof_buf = [0; 32];
+        hex::decode_to_slice(p, &mut proof_buf)?;
+        let mut hashes = [hash, proof_buf];
+        hashes.sort_unstable();
+        Ok::<[u8; 32], ContractError>(sha2::Sha256::digest(hashes.concat()).into())
+    })?;
+
+    let mut root_buf: [u8; 32] = [0; 32];
+    hex::decode_to_slice(merkle_root, &mut root_buf)?;
+    if root_buf != hash {
+        return Err(ContractError::VerificationFailed {});
+    }
+    Ok(())
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
-        QueryMsg::Config {} => to_binary(&query_config(deps)?),
-        QueryMsg::Staker { address } => to_binary(&query_staker(deps, address)?),
-        QueryMsg::ListStakers { start_after } => to_binary(&query_list_stakers(deps, start_after)?),
-        QueryMsg::GetHoleAmount { address } => to_binary(&query_get_hole_amount(deps, address)?),
+        QueryMsg::Config {} => to_json_binary(&query_config(deps)?),
+        QueryMsg::Staker { pool_id, address } => {
+            let address = deps.api.addr_validate(&address)?;
+            to_json_binary(&query_staker(deps, env, pool_id, address)?)
+        }
+        QueryMsg::ListStakers {
+            pool_id,
+            start_after,
+            limit,
+        } => to_json_binary(&query_list_stakers(deps, pool_id, start_after, limit)?),
+        QueryMsg::Position { position_id } => {
+            to_json_binary(&query_position(deps, position_id)?)
+        }
+        QueryMsg::GetHoleAmount { address } => {
+            let address = deps.api.addr_validate(&address)?;
+            to_json_binary(&query_get_hole_amount(deps, address)?)
+        }
+        QueryMsg::PendingReward { pool_id, address } => {
+            let address = deps.api.addr_validate(&address)?;
+            to_json_binary(&query_pending_reward(deps, env, pool_id, address)?)
+        }
+        QueryMsg::Pool { pool_id } => to_json_binary(&query_pool(deps, pool_id)?),
+        QueryMsg::Pools {} => to_json_binary(&query_pools(deps)?),
+        QueryMsg::Capacity { pool_id } => to_json_binary(&query_capacity(deps, pool_id)?),
+        QueryMsg::Delegation {} => to_json_binary(&query_delegation(deps)?),
+        QueryMsg::Unbondings { address } => {
+            let address = deps.api.addr_validate(&address)?;
+            to_json_binary(&query_unbondings(deps, address)?)
+        }
+        QueryMsg::RewardVesting { address } => {
+            let address = deps.api.addr_validate(&address)?;
+            to_json_binary(&query_reward_vesting(deps, env, address)?)
+        }
+        QueryMsg::LatestStage {} => to_json_binary(&LatestStageResponse {
+            latest_stage: LATEST_STAGE.may_load(deps.storage)?.unwrap_or_default(),
+        }),
+        QueryMsg::AirdropStage { stage } => to_json_binary(&query_airdrop_stage(deps, stage)?),
+        QueryMsg::IsClaimed { stage, address } => {
+            let address = deps.api.addr_validate(&address)?;
+            to_json_binary(&IsClaimedResponse {
+                is_claimed: AIRDROP_CLAIMED
+                    .may_load(deps.storage, (&address, stage))?
+                    .unwrap_or(false),
+            })
+        }
+        QueryMsg::OwnerOf {
+            token_id,
+            include_expired,
+        } => to_json_binary(&query_owner_of(deps, env, token_id, include_expired)?),
+        QueryMsg::Approval {
+            token_id,
+            spender,
+            include_expired,
+        } => to_json_binary(&query_approval(deps, env, token_id, spender, include_expired)?),
+        QueryMsg::Approvals {
+            token_id,
+            include_expired,
+        } => to_json_binary(&ApprovalsResponse {
+            approvals: query_owner_of(deps, env, token_id, include_expired)?.approvals,
+        }),
+        QueryMsg::AllOperators {
+            owner,
+            include_expired,
+            start_after,
+            limit,
+        } => to_json_binary(&query_all_operators(
+            deps,
+            env,
+            owner,
+            include_expired,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::NftInfo { token_id } => to_json_binary(&query_nft_info(deps, token_id)?),
+        QueryMsg::AllNftInfo {
+            token_id,
+            include_expired,
+        } => to_json_binary(&AllNftInfoResponse {
+            access: query_owner_of(deps, env, token_id.clone(), include_expired)?,
+            info: query_nft_info(deps, token_id)?,
+        }),
+        QueryMsg::Tokens {
+            owner,
+            start_after,
+            limit,
+        } => to_json_binary(&query_tokens(deps, owner, start_after, limit)?),
+        QueryMsg::AllTokens { start_after, limit } => {
+            to_json_binary(&query_all_tokens(deps, start_after, limit)?)
+        }
+        QueryMsg::NumTokens {} => to_json_binary(&NumTokensResponse {
+            count: NUM_TOKENS.load(deps.storage)?,
+        }),
+        QueryMsg::ContractInfo {} => to_json_binary(&ContractInfoResponse {
+            name: constants::POSITION_NFT_NAME.to_string(),
+            symbol: constants::POSITION_NFT_SYMBOL.to_string(),
+        }),
+        QueryMsg::PendingAdminActions {} => to_json_binary(&query_pending_admin_actions(deps)?),
     }
 }
 ///////////////////////////////////////////////////////// this func is called for getting the state of the contract  //////////////////////////////////
@@ -570,13 +3555,25 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     let cfg = CONFIG.load(deps.storage)?;
     Ok(ConfigResponse {
         owner: cfg.owner,
-        reward_token_denom: cfg.reward_token_denom.into(),
-        stake_token_address: cfg.stake_token_address.into(),
+        reward_token_denom: cfg.reward_token_denom,
+        stake_token_address: cfg.stake_token_address,
         reward_interval: cfg.reward_interval,
-        reward_artists_address: cfg.reward_artists_address.into(),
-        reward_burn_address: cfg.reward_burn_address.into(),
-        reward_charity_address: cfg.reward_charity_address.into(),
+        min_reward_interval: cfg.min_reward_interval,
+        max_reward_interval: cfg.max_reward_interval,
+        reward_artists_address: cfg.reward_artists_address,
+        reward_burn_address: cfg.reward_burn_address,
+        reward_charity_address: cfg.reward_charity_address,
         enabled: cfg.enabled,
+        validators: cfg.validators,
+        min_stake_amount: cfg.min_stake_amount,
+        max_positions_per_address: cfg.max_positions_per_address,
+        stake_on_behalf_allowlist: cfg.stake_on_behalf_allowlist,
+        reward_vesting_seconds: cfg.reward_vesting_seconds,
+        stake_paused: cfg.stake_paused,
+        claim_paused: cfg.claim_paused,
+        unstake_paused: cfg.unstake_paused,
+        emergency: cfg.emergency,
+        admin_timelock_seconds: cfg.admin_timelock_seconds,
     })
 }
 ///////////////////////////////////////////////////////// this func is called for getting the hole token amout  //////////////////////////////////
@@ -586,64 +3583,667 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
 ///         input params: contract address or wallet address
 ///     
 /// ////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
-pub fn query_get_hole_amount(deps: Deps, address: Addr) -> StdResult<TestBalanceResponse> {
+pub fn query_get_hole_amount(deps: Deps, address: Addr) -> StdResult<BalanceResponse> {
     let cfg = CONFIG.load(deps.storage)?;
 
-    let total = util::get_token_amount(
-        deps.querier,
-        Denom::Cw20(cfg.stake_token_address.clone()),
-        address.clone(),
-    )
-    .unwrap();
-    Ok(TestBalanceResponse { balance: total })
+    let response: cw20::BalanceResponse = deps.querier.query_wasm_smart(
+        cfg.stake_token_address,
+        &Cw20QueryMsg::Balance {
+            address: address.into(),
+        },
+    )?;
+    Ok(BalanceResponse {
+        balance: response.balance,
+    })
 }
 ///////////////////////////////////////////////////////// this func is called for getting the informations of stakers  //////////////////////////////////
 ///
 ///         
-///         Using this function, we can get anybody's all staking informations.
-///         input params: contract address or wallet address
+///         Using this function, we can get anybody's all staking informations in the pool.
+///         The totals are the staked amount, the reward claimable now and the amount whose lock has passed.
+///         For lp pools, the underlying token amounts of the whole staked lp amount are given too.
+///         input params: pool id
+///                       contract address or wallet address
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+fn query_staker(deps: Deps, env: Env, pool_id: u64, address: Addr) -> StdResult<StakerResponse> {
+    let pool = POOLS.load(deps.storage, pool_id)?;
+    let list = load_positions(deps.storage, pool_id, &address)?;
+    let now = env.block.time.seconds();
+
+    let mut total_staked = Uint128::zero();
+    let mut unlockable = Uint128::zero();
+    for staker in list.iter() {
+        total_staked = total_staked.checked_add(staker.amount)?;
+        if staker.last_time.saturating_add(staker.lock_type) <= now {
+            unlockable = unlockable.checked_add(staker.amount)?;
+        }
+    }
+
+    let underlying = match &pool.lp {
+        Some(lp) => {
+            let swap_info = util::get_swap_info(deps.querier, lp.swap_address.clone())?;
+            Some(util::get_underlying_amount(&swap_info, total_staked))
+        }
+        None => None,
+    };
+
+    let pending_reward = query_pending_reward(deps, env, pool_id, address.clone())?.total_reward;
+
+    Ok(StakerResponse {
+        address,
+        positions: list,
+        total_staked,
+        pending_reward,
+        unlockable,
+        underlying,
+    })
+}
+
+///////////////////////////////////////////////////////// this func is called for getting the pending reward of a staker  //////////////////////////////////
+///
+///         
+///         Using this function, we can get the reward which the staker would get when claiming at the current block time,
+///         without updating the staking records.
+///         input params: pool id
+///                       wallet address
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+fn query_pending_reward(
+    deps: Deps,
+    env: Env,
+    pool_id: u64,
+    address: Addr,
+) -> StdResult<PendingRewardResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let pool = POOLS.load(deps.storage, pool_id)?;
+    let list = load_positions(deps.storage, pool_id, &address)?;
+    let now = env.block.time.seconds();
+
+    let reward_index = DELEGATION.load(deps.storage)?.reward_index;
+
+    let mut total_reward = Uint128::zero();
+    let mut delegation_reward = Uint128::zero();
+    let mut positions = vec![];
+    for staker in list.iter() {
+        let reward = staker
+            .accrued
+            .checked_add(calc_reward(&cfg, &pool.tiers, staker, staker.reward_base, now)?)?;
+        total_reward = total_reward.checked_add(reward)?;
+        if pool.delegated {
+            delegation_reward += calc_delegation_reward(staker, reward_index);
+        }
+        positions.push(PositionRewardResponse {
+            position_id: staker.position_id,
+            amount: staker.amount,
+            reward,
+            unlock_in: staker.last_time.saturating_add(staker.lock_type).saturating_sub(now),
+        });
+    }
+
+    Ok(PendingRewardResponse {
+        pool_id,
+        address,
+        positions,
+        // the juno reward is the same amount as the hole reward for now (see execute_claim_reward)
+        // with the harvested staking reward of delegated pools
+        native_reward: total_reward + delegation_reward,
+        total_reward,
+    })
+}
+
+///////////////////////////////////////////////////////// this func is called for getting the informations of a staking pool  //////////////////////////////////
+///
+///         
+///         Using this function, we can get the assets, tiers and enable state of the pool.
+///         input params: pool id
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+fn query_pool(deps: Deps, pool_id: u64) -> StdResult<PoolResponse> {
+    let pool = POOLS.load(deps.storage, pool_id)?;
+    Ok(PoolResponse { pool_id, pool })
+}
+///////////////////////////////////////////////////////// this func is called for getting the informations of all staking pools  //////////////////////////////////
+///
+///         
+///         Using this function, we can get the assets, tiers and enable state of all pools.
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+fn query_pools(deps: Deps) -> StdResult<PoolsResponse> {
+    let pools: StdResult<Vec<PoolResponse>> = POOLS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(pool_id, pool)| PoolResponse { pool_id, pool }))
+        .collect();
+
+    Ok(PoolsResponse { pools: pools? })
+}
+///////////////////////////////////////////////////////// this func is called for getting the remaining staking capacity of a pool  //////////////////////////////////
+///
+///         
+///         Using this function, we can get the staked amount and the remaining capacity of the pool and every tier.
+///         The remaining capacity of a tier is limited by the remaining capacity of the pool as well.
+///         input params: pool id
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+fn query_capacity(deps: Deps, pool_id: u64) -> StdResult<CapacityResponse> {
+    let pool = POOLS.load(deps.storage, pool_id)?;
+    let total_staked = POOL_STAKED.may_load(deps.storage, pool_id)?.unwrap_or_default();
+
+    let mut tiers = vec![];
+    for tier in pool.tiers.iter() {
+        tiers.push(TierCapacityResponse {
+            lock_seconds: tier.lock_seconds,
+            staked: TIER_STAKED
+                .may_load(deps.storage, (pool_id, tier.lock_seconds))?
+                .unwrap_or_default(),
+            capacity: tier.capacity,
+            remaining: remaining_capacity(deps.storage, pool_id, &pool, tier)?,
+        });
+    }
+
+    Ok(CapacityResponse {
+        pool_id,
+        total_staked,
+        max_total_staked: pool.max_total_staked,
+        remaining: pool
+            .max_total_staked
+            .map(|max_total_staked| max_total_staked.saturating_sub(total_staked)),
+        tiers,
+    })
+}
+
+///////////////////////////////////////////////////////// this func is called for getting the delegation state  //////////////////////////////////
+///
+///         
+///         Using this function, we can get the validators, the total delegated amount and the reward index.
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+fn query_delegation(deps: Deps) -> StdResult<DelegationResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let state = DELEGATION.load(deps.storage)?;
+    Ok(DelegationResponse {
+        validators: cfg.validators,
+        total_delegated: state.total_delegated,
+        reward_index: state.reward_index,
+        pending_undelegation: state.pending_undelegation,
+        next_undelegation_time: state
+            .last_undelegation_time
+            .saturating_add(constants::UNDELEGATION_EPOCH_SECONDS),
+    })
+}
+///////////////////////////////////////////////////////// this func is called for getting the unbonding stake of a staker  //////////////////////////////////
+///
+///         
+///         Using this function, we can get the undelegated stake and its release time.
+///         The release time is not known until the batch of the unstake is undelegated,
+///         and the amount is the share of the batch in the undelegated amount once it is.
+///         input params: wallet address
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+fn query_unbondings(deps: Deps, address: Addr) -> StdResult<UnbondingsResponse> {
+    let unbondings = UNBONDINGS
+        .may_load(deps.storage, address)?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|unbonding| {
+            let batch = UNDELEGATION_BATCHES.may_load(deps.storage, unbonding.batch_id)?;
+            Ok(UnbondingEntryResponse {
+                batch_id: unbonding.batch_id,
+                amount: match &batch {
+                    Some(batch) => batch.undelegated.multiply_ratio(unbonding.amount, batch.amount),
+                    None => unbonding.amount,
+                },
+                release_time: batch.map(|batch| batch.release_time),
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(UnbondingsResponse { unbondings })
+}
+///////////////////////////////////////////////////////// this func is called for getting the queued admin actions  //////////////////////////////////
+///
+///         
+///         Using this function, the stakers can see the admin actions which will land and when.
+///         input params: none
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+fn query_pending_admin_actions(deps: Deps) -> StdResult<PendingAdminActionsResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let actions = ADMIN_ACTIONS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, action)| action))
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(PendingAdminActionsResponse {
+        timelock_seconds: cfg.admin_timelock_seconds,
+        actions,
+    })
+}
+///////////////////////////////////////////////////////// this func is called for getting the vesting rewards of a staker  //////////////////////////////////
+///
+///         
+///         Using this function, we can get the vested and unvested amounts of the claimed rewards.
+///         input params: wallet address
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+fn query_reward_vesting(deps: Deps, env: Env, address: Addr) -> StdResult<RewardVestingResponse> {
+    let now = env.block.time.seconds();
+    let vestings = REWARD_VESTING
+        .may_load(deps.storage, address.clone())?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|vesting| {
+            let vested = calc_vested(&vesting, now);
+            RewardVestingEntryResponse {
+                asset: vesting.asset,
+                amount: vesting.amount,
+                withdrawn: vesting.withdrawn,
+                vested,
+                unvested: vesting.amount - vested,
+                start_time: vesting.start_time,
+                end_time: vesting.end_time,
+            }
+        })
+        .collect();
+
+    Ok(RewardVestingResponse { address, vestings })
+}
+///////////////////////////////////////////////////////// this func is called for getting an airdrop stage  //////////////////////////////////
+///
+///         
+///         Using this function, we can get the merkle root, the schedule, the pool and the claimed amount of the stage.
+///         input params: stage
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+fn query_airdrop_stage(deps: Deps, stage: u8) -> StdResult<AirdropStageResponse> {
+    let airdrop = AIRDROP_STAGES.load(deps.storage, stage)?;
+    Ok(AirdropStageResponse {
+        stage,
+        merkle_root: airdrop.merkle_root,
+        start: airdrop.start,
+        expiration: airdrop.expiration,
+        pool_id: airdrop.pool_id,
+        total_amount: airdrop.total_amount,
+        claimed_amount: airdrop.claimed_amount,
+    })
+}
+
+///////////////////////////////////////////////////////// this func is called for getting the owner of a staking position nft  //////////////////////////////////
+///
+///         
+///         Using this function, we can get the owner and the approvals of the nft.
+///         input params: nft token id
+///                       include expired approvals flag
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+fn query_owner_of(
+    deps: Deps,
+    env: Env,
+    token_id: String,
+    include_expired: Option<bool>,
+) -> StdResult<OwnerOfResponse> {
+    let position = query_position(deps, parse_query_token_id(&token_id)?)?;
+    let include_expired = include_expired.unwrap_or(false);
+    let approvals = POSITION_APPROVALS
+        .may_load(deps.storage, position.position_id)?
+        .unwrap_or_default();
+
+    Ok(OwnerOfResponse {
+        owner: position.owner.to_string(),
+        approvals: approvals
+            .into_iter()
+            .filter(|approval| include_expired || !approval.expires.is_expired(&env.block))
+            .collect(),
+    })
+}
+///////////////////////////////////////////////////////// this func is called for getting the approval of a spender of the staking position nft  //////////////////////////////////
+///
+///         
+///         The query fails if the spender is not approved, as in cw721-base.
+///         input params: nft token id
+///                       spender address
+///                       include expired approval flag(optional, false by default)
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+fn query_approval(
+    deps: Deps,
+    env: Env,
+    token_id: String,
+    spender: String,
+    include_expired: Option<bool>,
+) -> StdResult<ApprovalResponse> {
+    // the owner is approved for its own nft
+    let owner_of = query_owner_of(deps, env, token_id, include_expired)?;
+    if owner_of.owner == spender {
+        return Ok(ApprovalResponse {
+            approval: Approval {
+                spender,
+                expires: Expiration::Never {},
+            },
+        });
+    }
+
+    owner_of
+        .approvals
+        .into_iter()
+        .find(|approval| approval.spender == spender)
+        .map(|approval| ApprovalResponse { approval })
+        .ok_or_else(|| StdError::not_found("Approval"))
+}
+///////////////////////////////////////////////////////// this func is called for getting the operators of an owner  //////////////////////////////////
+///
+///         
+///         input params: owner address
+///                       include expired operator flag(optional, false by default)
+///                       start operator address for getting the list of operators
+///                       limit
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+fn query_all_operators(
+    deps: Deps,
+    env: Env,
+    owner: String,
+    include_expired: Option<bool>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<OperatorsResponse> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let include_expired = include_expired.unwrap_or(false);
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_addr = start_after
+        .map(|operator| deps.api.addr_validate(&operator))
+        .transpose()?;
+    let start = start_addr.as_ref().map(Bound::exclusive);
+
+    let operators: StdResult<Vec<Approval>> = OPERATORS
+        .prefix(&owner_addr)
+        .range(deps.storage, start, None, Order::Ascending)
+        .filter(|item| {
+            include_expired
+                || item
+                    .as_ref()
+                    .map_or(true, |(_, expires)| !expires.is_expired(&env.block))
+        })
+        .take(limit)
+        .map(|item| {
+            item.map(|(spender, expires)| Approval {
+                spender: spender.to_string(),
+                expires,
+            })
+        })
+        .collect();
+
+    Ok(OperatorsResponse {
+        operators: operators?,
+    })
+}
+///////////////////////////////////////////////////////// this func is called for getting the staking record of a staking position nft  //////////////////////////////////
+///
+///         
+///         Using this function, we can get the pool and the staking record of the nft.
+///         input params: nft token id
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+fn query_nft_info(deps: Deps, token_id: String) -> StdResult<NftInfoResponse<Position>> {
+    Ok(NftInfoResponse {
+        token_uri: None,
+        extension: query_position(deps, parse_query_token_id(&token_id)?)?,
+    })
+}
+
+fn parse_query_token_id(token_id: &str) -> StdResult<u64> {
+    token_id
+        .parse::<u64>()
+        .map_err(|_| StdError::not_found("Position"))
+}
+///////////////////////////////////////////////////////// this func is called for getting the staking position nfts of an owner  //////////////////////////////////
+///
+///         
+///         input params: owner address
+///                       start token id for getting the list of nfts
+///                       limit
 ///     
 /// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+fn query_tokens(
+    deps: Deps,
+    owner: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<TokensResponse> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after
+        .map(|token_id| parse_query_token_id(&token_id))
+        .transpose()?
+        .map(Bound::exclusive);
 
-fn query_staker(deps: Deps, address: Addr) -> StdResult<Vec<StakerInfo>> {
-    let list = STAKERS
-        .load(deps.storage, address.clone())
-        .unwrap_or(vec![]);
-    Ok(list)
+    let tokens: StdResult<Vec<String>> = positions()
+        .prefix(owner_addr)
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|position_id| position_id.to_string()))
+        .collect();
+
+    Ok(TokensResponse { tokens: tokens? })
 }
+///////////////////////////////////////////////////////// this func is called for getting all staking position nfts  //////////////////////////////////
+///
+///         
+///         input params: start token id for getting the list of nfts
+///                       limit
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+fn query_all_tokens(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<TokensResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after
+        .map(|token_id| parse_query_token_id(&token_id))
+        .transpose()?
+        .map(Bound::exclusive);
+
+    let tokens: StdResult<Vec<String>> = positions()
+        .idx
+        .id
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, position)| position.position_id.to_string()))
+        .collect();
 
-fn map_staker(item: StdResult<(Addr, Vec<StakerInfo>)>) -> StdResult<Vec<StakerInfo>> {
-    item.map(|(_id, record)| record)
+    Ok(TokensResponse { tokens: tokens? })
+}
+///////////////////////////////////////////////////////// this func is called for getting a staking record  //////////////////////////////////
+///
+///         
+///         input params: position id
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+fn query_position(deps: Deps, position_id: u64) -> StdResult<Position> {
+    positions()
+        .idx
+        .id
+        .item(deps.storage, position_id)?
+        .map(|(_, position)| position)
+        .ok_or_else(|| StdError::not_found("Position"))
 }
 ///////////////////////////////////////////////////////// this func is called for getting the informations of all stakers  //////////////////////////////////
 ///
 ///         
-///         Using this function, we can get all staking informations for all stakers.
-///         input params: start wallet address for getting the list of stakers.
+///         Using this function, we can get all staking informations for all stakers of the pool.
+///         input params: pool id
+///                       start position id for getting the list of staking records.
+///                       limit
 ///     
 /// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
-fn query_list_stakers(deps: Deps, start_after: Option<String>) -> StdResult<StakerListResponse> {
-    let addr = maybe_addr(deps.api, start_after)?;
-    let start = addr.map(|addr| Bound::exclusive(addr.clone()));
+fn query_list_stakers(
+    deps: Deps,
+    pool_id: u64,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<StakerListResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
 
-    let stakers: StdResult<Vec<Vec<_>>> = STAKERS
+    let positions: StdResult<Vec<Position>> = positions()
+        .idx
+        .pool
+        .prefix(pool_id)
         .range(deps.storage, start, None, Order::Ascending)
-        .map(|item| map_staker(item))
+        .take(limit)
+        .map(|item| item.map(|(_, position)| position))
         .collect();
 
-    Ok(StakerListResponse { stakers: stakers? })
+    Ok(StakerListResponse {
+        positions: positions?,
+    })
 }
 ///////////////////////////////////////////////////////// this func is called for migration of the contract  //////////////////////////////////
 ///
-///         
-////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+///         The contract can not be migrated to an older version.
+///         The contract before 2.0.0 is converted to the pools and positions storage.
+///         input params: config fields to set(optional)
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
     let version = get_contract_version(deps.storage)?;
     if version.contract != CONTRACT_NAME {
         return Err(ContractError::CannotMigrate {
             previous_contract: version.contract,
         });
     }
-    Ok(Response::default())
+    let previous_version = parse_version(&version.version)?;
+    if previous_version > parse_version(CONTRACT_VERSION)? {
+        return Err(ContractError::CannotDowngrade {
+            previous_version: version.version,
+            version: CONTRACT_VERSION.to_string(),
+        });
+    }
+
+    let mut res = Response::new().add_attributes(vec![
+        attr("action", "migrate"),
+        attr("previous_version", version.version.clone()),
+        attr("version", CONTRACT_VERSION),
+    ]);
+    if previous_version < Version::new(2, 0, 0) {
+        let migrated = migrate_legacy_stakers(deps.storage)?;
+        res = res.add_attribute("migrated_positions", migrated.to_string());
+    }
+
+    let mut cfg = CONFIG.load(deps.storage)?;
+    if let Some(owner) = msg.owner {
+        cfg.owner = deps.api.addr_validate(&owner)?;
+    }
+    if let Some(min_reward_interval) = msg.min_reward_interval {
+        cfg.min_reward_interval = min_reward_interval;
+    }
+    if let Some(max_reward_interval) = msg.max_reward_interval {
+        cfg.max_reward_interval = max_reward_interval;
+    }
+    if cfg.min_reward_interval == 0 || cfg.min_reward_interval > cfg.max_reward_interval {
+        return Err(ContractError::InvalidInput {});
+    }
+    check_reward_interval(&cfg, cfg.reward_interval)?;
+    if let Some(min_stake_amount) = msg.min_stake_amount {
+        if min_stake_amount.is_zero() {
+            return Err(ContractError::InvalidInput {});
+        }
+        cfg.min_stake_amount = min_stake_amount;
+    }
+    if let Some(max_positions_per_address) = msg.max_positions_per_address {
+        if max_positions_per_address == 0 {
+            return Err(ContractError::InvalidInput {});
+        }
+        cfg.max_positions_per_address = max_positions_per_address;
+    }
+    if let Some(reward_vesting_seconds) = msg.reward_vesting_seconds {
+        check_reward_vesting(reward_vesting_seconds)?;
+        cfg.reward_vesting_seconds = reward_vesting_seconds;
+    }
+    if let Some(admin_timelock_seconds) = msg.admin_timelock_seconds {
+        if admin_timelock_seconds > constants::MAX_ADMIN_TIMELOCK_SECONDS {
+            return Err(ContractError::InvalidInput {});
+        }
+        cfg.admin_timelock_seconds = admin_timelock_seconds;
+    }
+    CONFIG.save(deps.storage, &cfg)?;
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(res)
+}
+
+fn parse_version(version: &str) -> Result<Version, ContractError> {
+    Version::parse(version).map_err(|_| ContractError::InvalidVersion {
+        version: version.to_string(),
+    })
+}
+///////////////////////////////////////////////////////// this func is called for converting the storage of the contract before 2.0.0 //////////////////////////////////
+///
+///         The config gets the default values for the new fields, and the interval bounds include the old reward interval.
+///         Every staking record of the stakers list becomes a position of the default pool.
+///         The reward of the record is recalculated from the last time as before, so nothing is accrued.
+///         The rank lists are kept as they are.
+///         Returns the number of positions.
+///     
+/// /////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+fn migrate_legacy_stakers(storage: &mut dyn Storage) -> Result<u64, ContractError> {
+    let legacy = LEGACY_CONFIG.load(storage)?;
+    let config = Config {
+        owner: legacy.owner,
+        stake_token_address: legacy.stake_token_address,
+        reward_charity_address: legacy.reward_charity_address,
+        reward_burn_address: legacy.reward_burn_address,
+        reward_artists_address: legacy.reward_artists_address,
+        reward_token_denom: legacy.reward_token_denom,
+        reward_interval: legacy.reward_interval,
+        min_reward_interval: legacy
+            .reward_interval
+            .min(constants::DEFAULT_MIN_REWARD_INTERVAL),
+        max_reward_interval: legacy
+            .reward_interval
+            .max(constants::DEFAULT_MAX_REWARD_INTERVAL),
+        enabled: legacy.enabled,
+        validators: vec![],
+        min_stake_amount: Uint128::from(constants::DEFAULT_MIN_STAKE_AMOUNT),
+        max_positions_per_address: constants::DEFAULT_MAX_POSITIONS_PER_ADDRESS,
+        stake_on_behalf_allowlist: None,
+        reward_vesting_seconds: 0,
+        stake_paused: false,
+        claim_paused: false,
+        unstake_paused: false,
+        emergency: false,
+        admin_timelock_seconds: 0,
+    };
+    CONFIG.save(storage, &config)?;
+    init_state(storage, &config)?;
+
+    let stakers = LEGACY_STAKERS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let mut migrated = 0u64;
+    for (address, list) in stakers {
+        for info in list {
+            create_position(
+                storage,
+                Position {
+                    position_id: 0,
+                    pool_id: 0,
+                    owner: address.clone(),
+                    asset: Denom::Cw20(config.stake_token_address.clone()),
+                    amount: info.amount,
+                    reward_base: info.amount,
+                    reward: info.reward,
+                    accrued: Uint128::zero(),
+                    last_time: info.last_time,
+                    lock_type: info.lock_type,
+                    reward_index: Decimal::zero(),
+                    received: false,
+                },
+            )?;
+            add_staked(storage, 0, info.lock_type, info.amount)?;
+            migrated += 1;
+        }
+        LEGACY_STAKERS.remove(storage, address);
+    }
+    Ok(migrated)
 }
@@ -1,5 +1,5 @@
 use cosmwasm_std::{StdError};
-use cw_utils::{Expiration, Scheduled};
+use cw_utils::{Expiration, PaymentError, Scheduled};
 use hex::FromHexError;
 use thiserror::Error;
 
@@ -11,12 +11,39 @@ pub enum ContractError {
     #[error("{0}")]
     Hex(#[from] FromHexError),
 
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
     #[error("Unauthorized")]
     Unauthorized {},
 
     #[error("Disabled")]
     Disabled {},
 
+    #[error("Staking is paused")]
+    StakePaused {},
+
+    #[error("Claiming is paused")]
+    ClaimPaused {},
+
+    #[error("Unstaking is paused")]
+    UnstakePaused {},
+
+    #[error("Not in emergency mode")]
+    NotEmergency {},
+
+    #[error("Admin action must be queued while the timelock is set")]
+    TimelockActive {},
+
+    #[error("Admin action can be executed at {executable_at}")]
+    TimelockNotExpired { executable_at: u64 },
+
+    #[error("Admin action not found")]
+    AdminActionNotFound {},
+
+    #[error("Reward interval must be between {min} and {max} seconds")]
+    InvalidRewardInterval { min: u64, max: u64 },
+
     #[error("InvalidInput")]
     InvalidInput {},
 
@@ -39,6 +66,24 @@ pub enum ContractError {
     #[error("No Staked")]
     NoStaked {},
 
+    #[error("No validators")]
+    NoValidators {},
+
+    #[error("Position not found")]
+    PositionNotFound {},
+
+    #[error("Approval expired")]
+    Expired {},
+
+    #[error("Staking capacity exceeded")]
+    CapacityExceeded {},
+
+    #[error("Tier of {lock_seconds} seconds still has staked positions")]
+    TierInUse { lock_seconds: u64 },
+
+    #[error("Lock can only be extended: current {current} seconds, requested {requested} seconds")]
+    LockNotExtended { current: u64, requested: u64 },
+
     #[error("Not Created Unstaking")]
     NotCreatedUnstaking {},
 
@@ -61,12 +106,36 @@ pub enum ContractError {
     #[error("Cannot migrate from different contract type: {previous_contract}")]
     CannotMigrate { previous_contract: String },
 
+    #[error("Cannot migrate from version {previous_version} to the older version {version}")]
+    CannotDowngrade {
+        previous_version: String,
+        version: String,
+    },
+
+    #[error("Invalid contract version: {version}")]
+    InvalidVersion { version: String },
+
     #[error("Airdrop stage {stage} expired at {expiration}")]
     StageExpired { stage: u8, expiration: Expiration },
 
     #[error("Airdrop stage {stage} begins at {start}")]
     StageNotBegun { stage: u8, start: Scheduled },
 
+    #[error("Airdrop stage {stage} is exhausted")]
+    StageExhausted { stage: u8 },
+
+    #[error("Airdrop stage {stage} has not expired")]
+    StageNotExpired { stage: u8 },
+
+    #[error("No more airdrop stages can be registered")]
+    TooManyStages {},
+
+    #[error("Pool {pool_id} still has stake")]
+    PoolHasStake { pool_id: u64 },
+
+    #[error("Verification failed")]
+    VerificationFailed {},
+
     #[error("Count {count}")]
     Count { count: u64 },
 }
@@ -1,29 +1,97 @@
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Addr, Binary, Decimal, Uint128};
 
 use cw20::{Cw20ReceiveMsg, Denom};
+use cw721::{
+    AllNftInfoResponse, ApprovalResponse, ApprovalsResponse, ContractInfoResponse,
+    NftInfoResponse, NumTokensResponse, OperatorsResponse, OwnerOfResponse, TokensResponse,
+};
+use cw_utils::{Expiration, Scheduled};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, JsonSchema)]
+#[cw_serde]
 pub struct InstantiateMsg {
-    pub stake_token_address: Addr,
-    pub reward_charity_address: Addr,
-    pub reward_burn_address: Addr,
-    pub reward_artists_address: Addr,
+    pub stake_token_address: String,
+    pub reward_charity_address: String,
+    pub reward_burn_address: String,
+    pub reward_artists_address: String,
     pub reward_token_denom: String,
     pub reward_interval: u64,
+    pub owner: Option<String>,
+    pub min_reward_interval: Option<u64>,
+    pub max_reward_interval: Option<u64>,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct StakerInfo {
-    pub address: Addr,
+#[cw_serde]
+pub struct Position {
+    pub position_id: u64,
+    pub pool_id: u64,
+    pub owner: Addr,
+    pub asset: Denom,
     pub amount: Uint128,
+    pub reward_base: Uint128,
     pub reward: Uint128,
+    pub accrued: Uint128,
     pub last_time: u64,
     pub lock_type: u64,
+    pub reward_index: Decimal,
+    pub received: bool,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[cw_serde]
+pub struct Unbonding {
+    pub batch_id: u64,
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct RewardVesting {
+    pub asset: Denom,
+    pub amount: Uint128,
+    pub withdrawn: Uint128,
+    pub start_time: u64,
+    pub end_time: u64,
+}
+
+#[cw_serde]
+pub struct Tier {
+    pub lock_seconds: u64,
+    pub apy: u64,
+    pub capacity: Option<Uint128>,
+}
+
+#[cw_serde]
+pub struct Pool {
+    pub stake_asset: Denom,
+    pub reward_asset: Denom,
+    pub native_reward_asset: Denom,
+    pub tiers: Vec<Tier>,
+    pub enabled: bool,
+    pub lp: Option<LpPool>,
+    pub delegated: bool,
+    pub max_total_staked: Option<Uint128>,
+}
+
+#[cw_serde]
+pub struct LpPool {
+    pub swap_address: Addr,
+    pub reward_on_underlying: bool,
+}
+
+#[cw_serde]
+pub enum UncheckedDenom {
+    Native(String),
+    Cw20(String),
+}
+
+#[cw_serde]
+pub struct UncheckedLpPool {
+    pub swap_address: String,
+    pub reward_on_underlying: bool,
+}
+
+#[cw_serde]
 pub struct RewardDistribution {
     pub juno_reward: bool,
     pub charity: u64,
@@ -31,36 +99,254 @@ pub struct RewardDistribution {
     pub artists: u64,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-#[serde(rename_all = "snake_case")]
+#[cw_serde]
+pub struct ConstantsUpdate {
+    pub reward_interval: Option<u64>,
+    pub reward_charity_address: Option<String>,
+    pub reward_burn_address: Option<String>,
+    pub reward_artists_address: Option<String>,
+    pub reward_token_denom: Option<String>,
+    pub stake_token_address: Option<String>,
+    pub swap_address: Option<SwapAddressUpdate>,
+}
+
+#[cw_serde]
+pub struct SwapAddressUpdate {
+    pub pool_id: u64,
+    pub swap_address: String,
+}
+
+#[cw_serde]
+pub enum AdminAction {
+    UpdateOwner { owner: String },
+    UpdateConstants(ConstantsUpdate),
+    WithdrawReward { amount: Uint128 },
+    WithdrawStake { amount: Uint128 },
+    UpdateAdminTimelock { timelock_seconds: u64 },
+    UpdatePoolTiers { pool_id: u64, tiers: Vec<Tier> },
+    UpdateRewardVesting { vesting_seconds: u64 },
+    UpdateValidators { validators: Vec<String> },
+}
+
+#[cw_serde]
+pub struct PendingAdminAction {
+    pub id: u64,
+    pub action: AdminAction,
+    pub queued_at: u64,
+    pub executable_at: u64,
+}
+
+#[cw_serde]
 pub enum ExecuteMsg {
-    UpdateOwner { owner: Addr },
+    UpdateOwner { owner: String },
     UpdateEnabled { enabled: bool },
-    UpdateConstants { reward_interval: u64 },
+    UpdatePaused {
+        stake_paused: Option<bool>,
+        claim_paused: Option<bool>,
+        unstake_paused: Option<bool>,
+    },
+    UpdateEmergency { emergency: bool },
+    UpdateConstants(ConstantsUpdate),
+    UpdateStakeLimits {
+        min_stake_amount: Uint128,
+        max_positions_per_address: u64,
+    },
+    UpdateStakeOnBehalfAllowlist { allowlist: Option<Vec<String>> },
+    UpdateRewardVesting { vesting_seconds: u64 },
+    QueueAdminAction { action: AdminAction },
+    ExecuteAdminAction { id: u64 },
+    CancelAdminAction { id: u64 },
+    CreatePool {
+        stake_asset: UncheckedDenom,
+        reward_asset: UncheckedDenom,
+        native_reward_asset: UncheckedDenom,
+        tiers: Vec<Tier>,
+        lp: Option<UncheckedLpPool>,
+        delegated: bool,
+        max_total_staked: Option<Uint128>,
+    },
+    UpdatePool {
+        pool_id: u64,
+        tiers: Option<Vec<Tier>>,
+        enabled: Option<bool>,
+    },
+    UpdatePoolCap {
+        pool_id: u64,
+        max_total_staked: Option<Uint128>,
+    },
     Receive(Cw20ReceiveMsg),
+    Stake {
+        pool_id: u64,
+        lock_type: u64,
+        recipient: Option<String>,
+    },
     WithdrawReward { amount: Uint128 },
     WithdrawStake { amount: Uint128 },
-    ClaimReward { distribution: RewardDistribution },
-    Unstake {},
+    ClaimReward {
+        pool_id: u64,
+        distribution: RewardDistribution,
+    },
+    Unstake { pool_id: u64 },
+    EmergencyWithdraw {},
+    TransferPosition {
+        position_id: u64,
+        recipient: String,
+    },
+    ExtendLock {
+        position_id: u64,
+        new_lock_type: u64,
+    },
+    MergePositions { position_ids: Vec<u64> },
+    SplitPosition {
+        position_id: u64,
+        amounts: Vec<Uint128>,
+    },
+    RegisterMerkleRoot {
+        merkle_root: String,
+        start: Option<Scheduled>,
+        expiration: Option<Expiration>,
+        pool_id: u64,
+    },
+    ClaimAirdrop {
+        stage: u8,
+        amount: Uint128,
+        proof: Vec<String>,
+        lock_type: u64,
+    },
+    WithdrawAirdropRemainder { stage: u8 },
+    UpdateValidators { validators: Vec<String> },
+    HarvestDelegationRewards {},
+    WithdrawUnbonded {},
+    WithdrawVested {},
+    TransferNft {
+        recipient: String,
+        token_id: String,
+    },
+    SendNft {
+        contract: String,
+        token_id: String,
+        msg: Binary,
+    },
+    Approve {
+        spender: String,
+        token_id: String,
+        expires: Option<Expiration>,
+    },
+    Revoke {
+        spender: String,
+        token_id: String,
+    },
+    ApproveAll {
+        operator: String,
+        expires: Option<Expiration>,
+    },
+    RevokeAll {
+        operator: String,
+    },
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-#[serde(rename_all = "snake_case")]
+#[cw_serde]
 pub enum ReceiveMsg {
-    Stake { lock_type: u64 },
+    Stake {
+        pool_id: u64,
+        lock_type: u64,
+        recipient: Option<String>,
+    },
+    RegisterMerkleRoot {
+        merkle_root: String,
+        start: Option<Scheduled>,
+        expiration: Option<Expiration>,
+        pool_id: u64,
+    },
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-#[serde(rename_all = "snake_case")]
+#[cw_serde]
+#[derive(QueryResponses)]
 pub enum QueryMsg {
+    #[returns(ConfigResponse)]
     Config {},
-    Staker { address: Addr },
-    ListStakers { start_after: Option<String> },
-    GetHoleAmount { address: Addr },
+    #[returns(StakerResponse)]
+    Staker { pool_id: u64, address: String },
+    #[returns(StakerListResponse)]
+    ListStakers {
+        pool_id: u64,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns(Position)]
+    Position { position_id: u64 },
+    #[returns(BalanceResponse)]
+    GetHoleAmount { address: String },
+    #[returns(PendingRewardResponse)]
+    PendingReward { pool_id: u64, address: String },
+    #[returns(PoolResponse)]
+    Pool { pool_id: u64 },
+    #[returns(PoolsResponse)]
+    Pools {},
+    #[returns(CapacityResponse)]
+    Capacity { pool_id: u64 },
+    #[returns(DelegationResponse)]
+    Delegation {},
+    #[returns(UnbondingsResponse)]
+    Unbondings { address: String },
+    #[returns(RewardVestingResponse)]
+    RewardVesting { address: String },
+    #[returns(LatestStageResponse)]
+    LatestStage {},
+    #[returns(AirdropStageResponse)]
+    AirdropStage { stage: u8 },
+    #[returns(IsClaimedResponse)]
+    IsClaimed { stage: u8, address: String },
+    #[returns(OwnerOfResponse)]
+    OwnerOf {
+        token_id: String,
+        include_expired: Option<bool>,
+    },
+    #[returns(ApprovalResponse)]
+    Approval {
+        token_id: String,
+        spender: String,
+        include_expired: Option<bool>,
+    },
+    #[returns(ApprovalsResponse)]
+    Approvals {
+        token_id: String,
+        include_expired: Option<bool>,
+    },
+    #[returns(OperatorsResponse)]
+    AllOperators {
+        owner: String,
+        include_expired: Option<bool>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(NftInfoResponse<Position>)]
+    NftInfo { token_id: String },
+    #[returns(AllNftInfoResponse<Position>)]
+    AllNftInfo {
+        token_id: String,
+        include_expired: Option<bool>,
+    },
+    #[returns(TokensResponse)]
+    Tokens {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(TokensResponse)]
+    AllTokens {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(NumTokensResponse)]
+    NumTokens {},
+    #[returns(ContractInfoResponse)]
+    ContractInfo {},
+    #[returns(PendingAdminActionsResponse)]
+    PendingAdminActions {},
 }
 
-#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
-#[serde(rename_all = "snake_case")]
+#[cw_serde]
 pub struct ConfigResponse {
     pub owner: Addr,
     pub stake_token_address: Addr,
@@ -69,15 +355,180 @@ pub struct ConfigResponse {
     pub reward_artists_address: Addr,
     pub reward_token_denom: String,
     pub reward_interval: u64,
+    pub min_reward_interval: u64,
+    pub max_reward_interval: u64,
     pub enabled: bool,
+    pub validators: Vec<String>,
+    pub min_stake_amount: Uint128,
+    pub max_positions_per_address: u64,
+    pub stake_on_behalf_allowlist: Option<Vec<Addr>>,
+    pub reward_vesting_seconds: u64,
+    pub stake_paused: bool,
+    pub claim_paused: bool,
+    pub unstake_paused: bool,
+    pub emergency: bool,
+    pub admin_timelock_seconds: u64,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct MigrateMsg {}
+#[cw_serde]
+#[derive(Default)]
+pub struct MigrateMsg {
+    pub owner: Option<String>,
+    pub min_reward_interval: Option<u64>,
+    pub max_reward_interval: Option<u64>,
+    pub min_stake_amount: Option<Uint128>,
+    pub max_positions_per_address: Option<u64>,
+    pub reward_vesting_seconds: Option<u64>,
+    pub admin_timelock_seconds: Option<u64>,
+}
 
-#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[cw_serde]
 pub struct StakerListResponse {
-    pub stakers: Vec<Vec<StakerInfo>>,
+    pub positions: Vec<Position>,
+}
+
+#[cw_serde]
+pub struct DelegationResponse {
+    pub validators: Vec<String>,
+    pub total_delegated: Uint128,
+    pub reward_index: Decimal,
+    pub pending_undelegation: Uint128,
+    pub next_undelegation_time: u64,
+}
+
+#[cw_serde]
+pub struct UnbondingEntryResponse {
+    pub batch_id: u64,
+    pub amount: Uint128,
+    pub release_time: Option<u64>,
+}
+
+#[cw_serde]
+pub struct UnbondingsResponse {
+    pub unbondings: Vec<UnbondingEntryResponse>,
+}
+
+#[cw_serde]
+pub struct RewardVestingEntryResponse {
+    pub asset: Denom,
+    pub amount: Uint128,
+    pub withdrawn: Uint128,
+    pub vested: Uint128,
+    pub unvested: Uint128,
+    pub start_time: u64,
+    pub end_time: u64,
+}
+
+#[cw_serde]
+pub struct RewardVestingResponse {
+    pub address: Addr,
+    pub vestings: Vec<RewardVestingEntryResponse>,
+}
+
+#[cw_serde]
+pub struct PendingAdminActionsResponse {
+    pub timelock_seconds: u64,
+    pub actions: Vec<PendingAdminAction>,
+}
+
+#[cw_serde]
+pub struct LatestStageResponse {
+    pub latest_stage: u8,
+}
+
+#[cw_serde]
+pub struct AirdropStageResponse {
+    pub stage: u8,
+    pub merkle_root: String,
+    pub start: Option<Scheduled>,
+    pub expiration: Option<Expiration>,
+    pub pool_id: u64,
+    pub total_amount: Uint128,
+    pub claimed_amount: Uint128,
+}
+
+#[cw_serde]
+pub struct IsClaimedResponse {
+    pub is_claimed: bool,
+}
+
+#[cw_serde]
+pub struct UnderlyingResponse {
+    pub token1_denom: Denom,
+    pub token1_amount: Uint128,
+    pub token2_denom: Denom,
+    pub token2_amount: Uint128,
+}
+
+#[cw_serde]
+pub struct StakerResponse {
+    pub address: Addr,
+    pub positions: Vec<Position>,
+    pub total_staked: Uint128,
+    pub pending_reward: Uint128,
+    pub unlockable: Uint128,
+    pub underlying: Option<UnderlyingResponse>,
+}
+
+#[cw_serde]
+pub struct PoolResponse {
+    pub pool_id: u64,
+    pub pool: Pool,
+}
+
+#[cw_serde]
+pub struct PoolsResponse {
+    pub pools: Vec<PoolResponse>,
+}
+
+#[cw_serde]
+pub struct TierCapacityResponse {
+    pub lock_seconds: u64,
+    pub staked: Uint128,
+    pub capacity: Option<Uint128>,
+    pub remaining: Option<Uint128>,
+}
+
+#[cw_serde]
+pub struct CapacityResponse {
+    pub pool_id: u64,
+    pub total_staked: Uint128,
+    pub max_total_staked: Option<Uint128>,
+    pub remaining: Option<Uint128>,
+    pub tiers: Vec<TierCapacityResponse>,
+}
+
+#[cw_serde]
+pub struct PositionRewardResponse {
+    pub position_id: u64,
+    pub amount: Uint128,
+    pub reward: Uint128,
+    pub unlock_in: u64,
+}
+
+#[cw_serde]
+pub struct PendingRewardResponse {
+    pub pool_id: u64,
+    pub address: Addr,
+    pub positions: Vec<PositionRewardResponse>,
+    pub total_reward: Uint128,
+    pub native_reward: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WasmswapQueryMsg {
+    Info {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WasmswapInfoResponse {
+    pub token1_reserve: Uint128,
+    pub token1_denom: Denom,
+    pub token2_reserve: Uint128,
+    pub token2_denom: Denom,
+    pub lp_token_supply: Uint128,
+    pub lp_token_address: String,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -90,7 +541,7 @@ pub struct Token2ForToken1PriceResponse {
     pub token1_amount: Uint128,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct TestBalanceResponse {
+#[cw_serde]
+pub struct BalanceResponse {
     pub balance: Uint128,
 }
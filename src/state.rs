@@ -1,10 +1,12 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{Addr, Decimal, Uint128};
 use cw20::Denom;
-use cw_storage_plus::{Item, Map};
-use crate::msg::{ StakerInfo};
+use cw721::Approval;
+use cw_utils::{Expiration, Scheduled};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex, UniqueIndex};
+use crate::msg::{PendingAdminAction, Pool, Position, RewardVesting, Unbonding};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
@@ -15,14 +17,174 @@ pub struct Config {
     pub reward_artists_address: Addr,
     pub reward_token_denom: String,
     pub reward_interval: u64,
-    pub enabled: bool
+    pub min_reward_interval: u64,
+    pub max_reward_interval: u64,
+    pub enabled: bool,
+    pub validators: Vec<String>,
+    pub min_stake_amount: Uint128,
+    pub max_positions_per_address: u64,
+    pub stake_on_behalf_allowlist: Option<Vec<Addr>>,
+    pub reward_vesting_seconds: u64,
+    pub stake_paused: bool,
+    pub claim_paused: bool,
+    pub unstake_paused: bool,
+    pub emergency: bool,
+    pub admin_timelock_seconds: u64,
+}
+
+// the config of the contract before 2.0.0
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyConfig {
+    pub owner: Addr,
+    pub stake_token_address: Addr,
+    pub reward_charity_address: Addr,
+    pub reward_burn_address: Addr,
+    pub reward_artists_address: Addr,
+    pub reward_token_denom: String,
+    pub reward_interval: u64,
+    pub enabled: bool,
+}
+
+// the staking record of the contract before 2.0.0, the lock type is in seconds
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyStakerInfo {
+    pub address: Addr,
+    pub amount: Uint128,
+    pub reward: Uint128,
+    pub last_time: u64,
+    pub lock_type: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DelegationState {
+    pub total_delegated: Uint128,
+    pub reward_index: Decimal,
+    // the unstaked amount of the open batch, still delegated until the batch is undelegated
+    pub pending_undelegation: Uint128,
+    pub batch_id: u64,
+    pub last_undelegation_time: u64,
+    // the undelegated stake which is released to the contract and not withdrawn yet
+    pub unbonded: Uint128,
+    pub next_release_batch: u64,
+    // the harvested staking reward which is not paid to the stakers yet
+    pub unpaid_reward: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UndelegationBatch {
+    // the unstaked amount of the staking records in the batch
+    pub amount: Uint128,
+    // the amount undelegated for the batch, less than the amount after a slash of the validators
+    pub undelegated: Uint128,
+    pub release_time: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AirdropStage {
+    pub merkle_root: String,
+    pub start: Option<Scheduled>,
+    pub expiration: Option<Expiration>,
+    pub pool_id: u64,
+    // the asset of the deposit, the stake asset of the pool at the time of registering
+    pub asset: Denom,
+    pub total_amount: Uint128,
+    pub claimed_amount: Uint128,
+}
+
+pub struct PositionIndexes<'a> {
+    pub id: UniqueIndex<'a, u64, Position, (Addr, u64)>,
+    pub pool: UniqueIndex<'a, (u64, u64), Position, (Addr, u64)>,
+    pub pool_owner: MultiIndex<'a, (u64, Addr), Position, (Addr, u64)>,
+}
+
+impl<'a> IndexList<Position> for PositionIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Position>> + '_> {
+        let v: Vec<&dyn Index<Position>> = vec![&self.id, &self.pool, &self.pool_owner];
+        Box::new(v.into_iter())
+    }
 }
 
 pub const CONFIG_KEY: &str = "config";
 pub const CONFIG: Item<Config> = Item::new(CONFIG_KEY);
+pub const LEGACY_CONFIG: Item<LegacyConfig> = Item::new(CONFIG_KEY);
+
+pub const LEGACY_STAKERS_KEY: &str = "stakers";
+pub const LEGACY_STAKERS: Map<Addr, Vec<LegacyStakerInfo>> = Map::new(LEGACY_STAKERS_KEY);
+
+pub const POSITIONS_KEY: &str = "positions";
+pub const POSITIONS_ID_KEY: &str = "positions__id";
+pub const POSITIONS_POOL_KEY: &str = "positions__pool";
+pub const POSITIONS_POOL_OWNER_KEY: &str = "positions__pool_owner";
+pub fn positions<'a>() -> IndexedMap<'a, (Addr, u64), Position, PositionIndexes<'a>> {
+    let indexes = PositionIndexes {
+        id: UniqueIndex::new(|position| position.position_id, POSITIONS_ID_KEY),
+        pool: UniqueIndex::new(
+            |position| (position.pool_id, position.position_id),
+            POSITIONS_POOL_KEY,
+        ),
+        pool_owner: MultiIndex::new(
+            |_pk, position| (position.pool_id, position.owner.clone()),
+            POSITIONS_KEY,
+            POSITIONS_POOL_OWNER_KEY,
+        ),
+    };
+    IndexedMap::new(POSITIONS_KEY, indexes)
+}
+
+pub const POSITION_COUNT_KEY: &str = "position_count";
+pub const POSITION_COUNT: Item<u64> = Item::new(POSITION_COUNT_KEY);
+
+pub const POOL_STAKED_KEY: &str = "pool_staked";
+pub const POOL_STAKED: Map<u64, Uint128> = Map::new(POOL_STAKED_KEY);
+
+pub const TIER_STAKED_KEY: &str = "tier_staked";
+pub const TIER_STAKED: Map<(u64, u64), Uint128> = Map::new(TIER_STAKED_KEY);
+
+pub const DELEGATION_KEY: &str = "delegation";
+pub const DELEGATION: Item<DelegationState> = Item::new(DELEGATION_KEY);
+
+pub const UNBONDINGS_KEY: &str = "unbondings";
+pub const UNBONDINGS: Map<Addr, Vec<Unbonding>> = Map::new(UNBONDINGS_KEY);
+
+pub const UNDELEGATION_BATCHES_KEY: &str = "undelegation_batches";
+pub const UNDELEGATION_BATCHES: Map<u64, UndelegationBatch> = Map::new(UNDELEGATION_BATCHES_KEY);
+
+pub const REWARD_VESTING_KEY: &str = "reward_vesting";
+pub const REWARD_VESTING: Map<Addr, Vec<RewardVesting>> = Map::new(REWARD_VESTING_KEY);
+
+// the claimed reward which is not withdrawn yet, by the denom key of the asset
+pub const REWARD_VESTING_TOTAL_KEY: &str = "reward_vesting_total";
+pub const REWARD_VESTING_TOTAL: Map<&str, Uint128> = Map::new(REWARD_VESTING_TOTAL_KEY);
+
+pub const POSITION_APPROVALS_KEY: &str = "position_approvals";
+pub const POSITION_APPROVALS: Map<u64, Vec<Approval>> = Map::new(POSITION_APPROVALS_KEY);
+
+pub const NUM_TOKENS_KEY: &str = "num_tokens";
+pub const NUM_TOKENS: Item<u64> = Item::new(NUM_TOKENS_KEY);
+
+pub const OPERATORS_KEY: &str = "operators";
+pub const OPERATORS: Map<(&Addr, &Addr), Expiration> = Map::new(OPERATORS_KEY);
+
+pub const LATEST_STAGE_KEY: &str = "latest_stage";
+pub const LATEST_STAGE: Item<u8> = Item::new(LATEST_STAGE_KEY);
+
+pub const AIRDROP_STAGES_KEY: &str = "airdrop_stages";
+pub const AIRDROP_STAGES: Map<u8, AirdropStage> = Map::new(AIRDROP_STAGES_KEY);
+
+pub const AIRDROP_CLAIMED_KEY: &str = "airdrop_claimed";
+pub const AIRDROP_CLAIMED: Map<(&Addr, u8), bool> = Map::new(AIRDROP_CLAIMED_KEY);
+
+pub const ADMIN_ACTION_COUNT_KEY: &str = "admin_action_count";
+pub const ADMIN_ACTION_COUNT: Item<u64> = Item::new(ADMIN_ACTION_COUNT_KEY);
+
+pub const ADMIN_ACTIONS_KEY: &str = "admin_actions";
+pub const ADMIN_ACTIONS: Map<u64, PendingAdminAction> = Map::new(ADMIN_ACTIONS_KEY);
+
+pub const POOL_COUNT_KEY: &str = "pool_count";
+pub const POOL_COUNT: Item<u64> = Item::new(POOL_COUNT_KEY);
 
-pub const STAKERS_KEY: &str = "stakers";
-pub const STAKERS: Map<Addr, Vec<StakerInfo>> = Map::new(STAKERS_KEY);
+pub const POOLS_KEY: &str = "pools";
+pub const POOLS: Map<u64, Pool> = Map::new(POOLS_KEY);
 
 pub const RANK_STAKERS_KEY: &str = "rank_stakers";
 pub const RANK_STAKERS: Map<u8, (Addr, Uint128)> = Map::new(RANK_STAKERS_KEY);
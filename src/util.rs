@@ -1,13 +1,11 @@
 use crate::error::ContractError;
-use crate::state::CONFIG;
+use crate::msg::{UnderlyingResponse, WasmswapInfoResponse, WasmswapQueryMsg};
 use cosmwasm_std::{
-    to_binary, Addr, BalanceResponse as NativeBalanceResponse, BankMsg, BankQuery, Coin, CosmosMsg,
-    QuerierWrapper, QueryRequest, Response, StdResult, Storage, Uint128, WasmMsg, WasmQuery,
+    to_json_binary, Addr, BalanceResponse as NativeBalanceResponse, BankMsg, BankQuery, Coin,
+    CosmosMsg, QuerierWrapper, QueryRequest, StdResult, Uint128, WasmMsg, WasmQuery,
 };
-use cw20::{Balance, BalanceResponse as CW20BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Denom};
+use cw20::{BalanceResponse as CW20BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Denom};
 // use stockpool::msg::{ExecuteMsg as WasmswapExecuteMsg, QueryMsg as WasmswapQueryMsg, Token1ForToken2PriceResponse, Token2ForToken1PriceResponse, InfoResponse as WasmswapInfoResponse, TokenSelect};
-use schemars::JsonSchema;
-use serde::{Deserialize, Serialize};
 pub const NORMAL_DECIMAL: u128 = 1000000u128;
 pub const THRESHOLD: u128 = 3000000u128;
 
@@ -18,23 +16,23 @@ pub fn transfer_native_token_message(
 ) -> Result<CosmosMsg, ContractError> {
     match denom.clone() {
         Denom::Native(native_str) => {
-            return Ok(BankMsg::Send {
+            Ok(BankMsg::Send {
                 to_address: receiver.clone().into(),
                 amount: vec![Coin{
                     denom: native_str,
                     amount
                 }]
-            }.into());
+            }.into())
         },
         Denom::Cw20(cw20_address) => {
-            return Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            Ok(CosmosMsg::Wasm(WasmMsg::Execute {
                 contract_addr: cw20_address.clone().into(),
                 funds: vec![],
-                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
                     recipient: receiver.clone().into(),
                     amount
                 })?,
-            }));
+            }))
         }
     }
 }
@@ -46,28 +44,35 @@ pub fn transfer_token_message(
 ) -> Result<CosmosMsg, ContractError> {
     match denom.clone() {
         Denom::Native(native_str) => {
-            return Ok(BankMsg::Send {
+            Ok(BankMsg::Send {
                 to_address: receiver.clone().into(),
                 amount: vec![Coin {
                     denom: native_str,
                     amount,
                 }],
             }
-            .into());
+            .into())
         }
         Denom::Cw20(cw20_address) => {
-            return Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            Ok(CosmosMsg::Wasm(WasmMsg::Execute {
                 contract_addr: cw20_address.clone().into(),
                 funds: vec![],
-                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
                     recipient: receiver.clone().into(),
                     amount,
                 })?,
-            }));
+            }))
         }
     }
 }
 
+pub fn denom_key(denom: &Denom) -> String {
+    match denom {
+        Denom::Native(native_str) => native_str.clone(),
+        Denom::Cw20(cw20_address) => cw20_address.to_string(),
+    }
+}
+
 pub fn get_token_amount(
     querier: QuerierWrapper,
     denom: Denom,
@@ -80,17 +85,41 @@ pub fn get_token_amount(
                     address: contract_addr.clone().into(),
                     denom: native_str,
                 }))?;
-            return Ok(native_response.amount.amount);
+            Ok(native_response.amount.amount)
         }
         Denom::Cw20(cw20_address) => {
             let balance_response: CW20BalanceResponse =
                 querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
                     contract_addr: cw20_address.clone().into(),
-                    msg: to_binary(&Cw20QueryMsg::Balance {
+                    msg: to_json_binary(&Cw20QueryMsg::Balance {
                         address: contract_addr.clone().into(),
                     })?,
                 }))?;
-            return Ok(balance_response.balance);
+            Ok(balance_response.balance)
         }
     }
 }
+
+pub fn get_swap_info(querier: QuerierWrapper, swap_address: Addr) -> StdResult<WasmswapInfoResponse> {
+    querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: swap_address.into(),
+        msg: to_json_binary(&WasmswapQueryMsg::Info {})?,
+    }))
+}
+
+pub fn get_underlying_amount(info: &WasmswapInfoResponse, lp_amount: Uint128) -> UnderlyingResponse {
+    let (token1_amount, token2_amount) = if info.lp_token_supply.is_zero() {
+        (Uint128::zero(), Uint128::zero())
+    } else {
+        (
+            lp_amount.multiply_ratio(info.token1_reserve, info.lp_token_supply),
+            lp_amount.multiply_ratio(info.token2_reserve, info.lp_token_supply),
+        )
+    };
+    UnderlyingResponse {
+        token1_denom: info.token1_denom.clone(),
+        token1_amount,
+        token2_denom: info.token2_denom.clone(),
+        token2_amount,
+    }
+}
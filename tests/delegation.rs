@@ -0,0 +1,870 @@
+use cosmwasm_std::testing::{
+    mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR,
+};
+use cosmwasm_std::{
+    attr, coin, coins, from_json, Addr, BankMsg, CosmosMsg, Decimal, DistributionMsg, Env,
+    FullDelegation, OwnedDeps, StakingMsg, Timestamp, Uint128, Validator,
+};
+use incentive::constants;
+use incentive::contract::{execute, instantiate, query};
+use incentive::msg::{
+    DelegationResponse, ExecuteMsg, InstantiateMsg, PendingRewardResponse, QueryMsg,
+    RewardDistribution, StakerResponse, UnbondingEntryResponse, UnbondingsResponse, UncheckedDenom,
+};
+use incentive::ContractError;
+
+const OWNER: &str = "owner";
+const USER: &str = "user";
+const CHARITY: &str = "charity";
+const DENOM: &str = "ujuno";
+const VALIDATOR1: &str = "junovaloper1";
+const VALIDATOR2: &str = "junovaloper2";
+const POOL_ID: u64 = 1;
+
+fn validator(address: &str) -> Validator {
+    Validator {
+        address: address.to_string(),
+        commission: Decimal::percent(5),
+        max_commission: Decimal::percent(10),
+        max_change_rate: Decimal::percent(1),
+    }
+}
+
+fn delegation(validator: &str, amount: u128, rewards: u128) -> FullDelegation {
+    FullDelegation {
+        delegator: Addr::unchecked(MOCK_CONTRACT_ADDR),
+        validator: validator.to_string(),
+        amount: coin(amount, DENOM),
+        can_redelegate: coin(amount, DENOM),
+        accumulated_rewards: coins(rewards, DENOM),
+    }
+}
+
+fn update_delegations(
+    deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    delegations: &[FullDelegation],
+) {
+    deps.querier.update_staking(
+        DENOM,
+        &[validator(VALIDATOR1), validator(VALIDATOR2)],
+        delegations,
+    );
+}
+
+fn setup() -> (OwnedDeps<MockStorage, MockApi, MockQuerier>, Env) {
+    let mut deps = mock_dependencies();
+    update_delegations(&mut deps, &[]);
+    let env = mock_env();
+
+    instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OWNER, &[]),
+        InstantiateMsg {
+            stake_token_address: "hole".to_string(),
+            reward_charity_address: CHARITY.to_string(),
+            reward_burn_address: "burn".to_string(),
+            reward_artists_address: "artists".to_string(),
+            reward_token_denom: DENOM.to_string(),
+            reward_interval: constants::ONE_YEAR_SECONDS,
+            owner: None,
+            min_reward_interval: None,
+            max_reward_interval: None,
+        },
+    )
+    .unwrap();
+
+    // the amounts of the tests are below the default minimum stake
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OWNER, &[]),
+        ExecuteMsg::UpdateStakeLimits {
+            min_stake_amount: Uint128::new(1),
+            max_positions_per_address: constants::DEFAULT_MAX_POSITIONS_PER_ADDRESS,
+        },
+    )
+    .unwrap();
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OWNER, &[]),
+        ExecuteMsg::UpdateValidators {
+            validators: vec![VALIDATOR1.to_string(), VALIDATOR2.to_string()],
+        },
+    )
+    .unwrap();
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OWNER, &[]),
+        ExecuteMsg::CreatePool {
+            stake_asset: UncheckedDenom::Native(DENOM.to_string()),
+            reward_asset: UncheckedDenom::Cw20("hole".to_string()),
+            native_reward_asset: UncheckedDenom::Native(DENOM.to_string()),
+            tiers: incentive::contract::default_tiers(),
+            lp: None,
+            delegated: true,
+            max_total_staked: None,
+        },
+    )
+    .unwrap();
+
+    (deps, env)
+}
+
+fn stake(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>, env: Env, amount: u128) {
+    execute(
+        deps.as_mut(),
+        env,
+        mock_info(USER, &coins(amount, DENOM)),
+        ExecuteMsg::Stake {
+            pool_id: POOL_ID,
+            lock_type: constants::LOCKED_ONE_MONTH,
+            recipient: None,
+        },
+    )
+    .unwrap();
+}
+
+fn query_delegation(deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>) -> DelegationResponse {
+    from_json(query(deps.as_ref(), mock_env(), QueryMsg::Delegation {}).unwrap()).unwrap()
+}
+
+#[test]
+fn stake_is_delegated_to_validators() {
+    let (mut deps, env) = setup();
+
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info(USER, &coins(1001, DENOM)),
+        ExecuteMsg::Stake {
+            pool_id: POOL_ID,
+            lock_type: constants::LOCKED_ONE_MONTH,
+            recipient: None,
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        res.messages
+            .into_iter()
+            .map(|msg| msg.msg)
+            .collect::<Vec<_>>(),
+        vec![
+            CosmosMsg::Staking(StakingMsg::Delegate {
+                validator: VALIDATOR1.to_string(),
+                amount: coin(501, DENOM),
+            }),
+            CosmosMsg::Staking(StakingMsg::Delegate {
+                validator: VALIDATOR2.to_string(),
+                amount: coin(500, DENOM),
+            }),
+        ]
+    );
+    assert_eq!(query_delegation(&deps).total_delegated, Uint128::new(1001));
+}
+
+#[test]
+fn stake_without_validators_fails() {
+    let (mut deps, env) = setup();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OWNER, &[]),
+        ExecuteMsg::UpdateValidators { validators: vec![] },
+    )
+    .unwrap();
+
+    let err = execute(
+        deps.as_mut(),
+        env,
+        mock_info(USER, &coins(1000, DENOM)),
+        ExecuteMsg::Stake {
+            pool_id: POOL_ID,
+            lock_type: constants::LOCKED_ONE_MONTH,
+            recipient: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NoValidators {});
+}
+
+#[test]
+fn harvested_rewards_follow_reward_distribution() {
+    let (mut deps, env) = setup();
+    stake(&mut deps, env.clone(), 1000);
+
+    update_delegations(
+        &mut deps,
+        &[
+            delegation(VALIDATOR1, 500, 60),
+            delegation(VALIDATOR2, 500, 40),
+        ],
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(USER, &[]),
+        ExecuteMsg::ClaimReward {
+            pool_id: POOL_ID,
+            distribution: RewardDistribution {
+                juno_reward: true,
+                charity: 10,
+                burn: 0,
+                artists: 0,
+            },
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        res.messages
+            .into_iter()
+            .map(|msg| msg.msg)
+            .collect::<Vec<_>>(),
+        vec![
+            CosmosMsg::Distribution(DistributionMsg::WithdrawDelegatorReward {
+                validator: VALIDATOR1.to_string(),
+            }),
+            CosmosMsg::Distribution(DistributionMsg::WithdrawDelegatorReward {
+                validator: VALIDATOR2.to_string(),
+            }),
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: CHARITY.to_string(),
+                amount: coins(10, DENOM),
+            }),
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: USER.to_string(),
+                amount: coins(90, DENOM),
+            }),
+        ]
+    );
+    assert_eq!(query_delegation(&deps).reward_index, Decimal::percent(10));
+}
+
+#[test]
+fn harvest_is_shared_by_delegated_amount() {
+    let (mut deps, env) = setup();
+    stake(&mut deps, env.clone(), 3000);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("other", &coins(1000, DENOM)),
+        ExecuteMsg::Stake {
+            pool_id: POOL_ID,
+            lock_type: constants::LOCKED_ONE_MONTH,
+            recipient: None,
+        },
+    )
+    .unwrap();
+
+    update_delegations(
+        &mut deps,
+        &[
+            delegation(VALIDATOR1, 2000, 200),
+            delegation(VALIDATOR2, 2000, 200),
+        ],
+    );
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::HarvestDelegationRewards {},
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert!(res.attributes.contains(&attr("harvested_amount", "400")));
+
+    let pending: incentive::msg::PendingRewardResponse = from_json(
+        query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::PendingReward {
+                pool_id: POOL_ID,
+                address: USER.to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(pending.native_reward, Uint128::new(300));
+
+    let pending: incentive::msg::PendingRewardResponse = from_json(
+        query(
+            deps.as_ref(),
+            env,
+            QueryMsg::PendingReward {
+                pool_id: POOL_ID,
+                address: "other".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(pending.native_reward, Uint128::new(100));
+}
+
+#[test]
+fn unstake_undelegates_and_unbonds() {
+    let (mut deps, mut env) = setup();
+    stake(&mut deps, env.clone(), 1000);
+    update_delegations(
+        &mut deps,
+        &[
+            delegation(VALIDATOR1, 500, 0),
+            delegation(VALIDATOR2, 500, 0),
+        ],
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(USER, &[]),
+        ExecuteMsg::Unstake { pool_id: POOL_ID },
+    )
+    .unwrap();
+    let msgs: Vec<CosmosMsg> = res.messages.into_iter().map(|msg| msg.msg).collect();
+    assert!(msgs.contains(&CosmosMsg::Staking(StakingMsg::Undelegate {
+        validator: VALIDATOR1.to_string(),
+        amount: coin(500, DENOM),
+    })));
+    assert!(msgs.contains(&CosmosMsg::Staking(StakingMsg::Undelegate {
+        validator: VALIDATOR2.to_string(),
+        amount: coin(500, DENOM),
+    })));
+    assert_eq!(query_delegation(&deps).total_delegated, Uint128::zero());
+
+    let unbondings: UnbondingsResponse = from_json(
+        query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Unbondings {
+                address: USER.to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(unbondings.unbondings.len(), 1);
+    assert_eq!(unbondings.unbondings[0].amount, Uint128::new(1000));
+
+    // still unbonding
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(USER, &[]),
+        ExecuteMsg::WithdrawUnbonded {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NotCreatedUnstaking {});
+
+    env.block.time = env.block.time.plus_seconds(constants::UNBONDING_SECONDS);
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, coins(1000, DENOM));
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info(USER, &[]),
+        ExecuteMsg::WithdrawUnbonded {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: USER.to_string(),
+            amount: coins(1000, DENOM),
+        })
+    );
+}
+
+#[test]
+fn unstake_pays_the_delegation_reward() {
+    let (mut deps, env) = setup();
+    stake(&mut deps, env.clone(), 1000);
+    update_delegations(
+        &mut deps,
+        &[
+            delegation(VALIDATOR1, 500, 60),
+            delegation(VALIDATOR2, 500, 40),
+        ],
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info(USER, &[]),
+        ExecuteMsg::Unstake { pool_id: POOL_ID },
+    )
+    .unwrap();
+    assert!(res.attributes.contains(&attr("delegation_reward", "100")));
+
+    // the reward is withdrawn from the validators before it is sent
+    let msgs: Vec<CosmosMsg> = res.messages.into_iter().map(|msg| msg.msg).collect();
+    assert_eq!(
+        msgs[..2],
+        [
+            CosmosMsg::Distribution(DistributionMsg::WithdrawDelegatorReward {
+                validator: VALIDATOR1.to_string(),
+            }),
+            CosmosMsg::Distribution(DistributionMsg::WithdrawDelegatorReward {
+                validator: VALIDATOR2.to_string(),
+            }),
+        ]
+    );
+    assert_eq!(
+        msgs.last(),
+        Some(&CosmosMsg::Bank(BankMsg::Send {
+            to_address: USER.to_string(),
+            amount: coins(100, DENOM),
+        }))
+    );
+}
+
+#[test]
+fn emergency_withdraw_unbonds_delegated_stake() {
+    let (mut deps, env) = setup();
+    stake(&mut deps, env.clone(), 1000);
+    update_delegations(
+        &mut deps,
+        &[
+            delegation(VALIDATOR1, 500, 0),
+            delegation(VALIDATOR2, 500, 0),
+        ],
+    );
+
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(USER, &[]),
+        ExecuteMsg::EmergencyWithdraw {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NotEmergency {});
+
+    for msg in [
+        ExecuteMsg::UpdateEnabled { enabled: false },
+        ExecuteMsg::UpdateEmergency { emergency: true },
+    ] {
+        execute(deps.as_mut(), env.clone(), mock_info(OWNER, &[]), msg).unwrap();
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(USER, &[]),
+        ExecuteMsg::EmergencyWithdraw {},
+    )
+    .unwrap();
+    let msgs: Vec<CosmosMsg> = res.messages.into_iter().map(|msg| msg.msg).collect();
+    assert!(msgs.contains(&CosmosMsg::Staking(StakingMsg::Undelegate {
+        validator: VALIDATOR1.to_string(),
+        amount: coin(500, DENOM),
+    })));
+    assert!(res.attributes.contains(&attr("unbonding_amount", "1000")));
+    assert_eq!(query_delegation(&deps).total_delegated, Uint128::zero());
+
+    let unbondings: UnbondingsResponse = from_json(
+        query(
+            deps.as_ref(),
+            env,
+            QueryMsg::Unbondings {
+                address: USER.to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(unbondings.unbondings[0].amount, Uint128::new(1000));
+}
+
+#[test]
+fn merge_keeps_the_delegation_reward_with_the_weighted_index() {
+    let (mut deps, env) = setup();
+    stake(&mut deps, env.clone(), 1000);
+    update_delegations(
+        &mut deps,
+        &[
+            delegation(VALIDATOR1, 500, 60),
+            delegation(VALIDATOR2, 500, 40),
+        ],
+    );
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::HarvestDelegationRewards {},
+    )
+    .unwrap();
+    // the second record starts from the harvested reward index
+    update_delegations(
+        &mut deps,
+        &[
+            delegation(VALIDATOR1, 500, 0),
+            delegation(VALIDATOR2, 500, 0),
+        ],
+    );
+    stake(&mut deps, env.clone(), 1000);
+
+    let pending = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>| -> PendingRewardResponse {
+        from_json(
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::PendingReward {
+                    pool_id: POOL_ID,
+                    address: USER.to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+    assert_eq!(pending(&deps).native_reward, Uint128::new(100));
+
+    execute(
+        deps.as_mut(),
+        env,
+        mock_info(USER, &[]),
+        ExecuteMsg::MergePositions {
+            position_ids: vec![0, 1],
+        },
+    )
+    .unwrap();
+
+    let staker: StakerResponse = from_json(
+        query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Staker {
+                pool_id: POOL_ID,
+                address: USER.to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(staker.positions.len(), 1);
+    assert_eq!(staker.positions[0].amount, Uint128::new(2000));
+    assert_eq!(staker.positions[0].reward_index, Decimal::permille(50));
+    assert_eq!(pending(&deps).native_reward, Uint128::new(100));
+}
+
+fn unstake_as(
+    deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    env: Env,
+    address: &str,
+) -> Vec<CosmosMsg> {
+    execute(
+        deps.as_mut(),
+        env,
+        mock_info(address, &[]),
+        ExecuteMsg::Unstake { pool_id: POOL_ID },
+    )
+    .unwrap()
+    .messages
+    .into_iter()
+    .map(|msg| msg.msg)
+    .collect()
+}
+
+fn withdraw_unbonded(
+    deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    env: Env,
+    address: &str,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    execute(
+        deps.as_mut(),
+        env,
+        mock_info(address, &[]),
+        ExecuteMsg::WithdrawUnbonded {},
+    )
+    .map(|res| res.messages.into_iter().map(|msg| msg.msg).collect())
+}
+
+fn query_unbondings(
+    deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    address: &str,
+) -> Vec<UnbondingEntryResponse> {
+    let res: UnbondingsResponse = from_json(
+        query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Unbondings {
+                address: address.to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    res.unbondings
+}
+
+fn undelegate_msg(validator: &str, amount: u128) -> CosmosMsg {
+    CosmosMsg::Staking(StakingMsg::Undelegate {
+        validator: validator.to_string(),
+        amount: coin(amount, DENOM),
+    })
+}
+
+fn send_msg(address: &str, amount: u128) -> CosmosMsg {
+    CosmosMsg::Bank(BankMsg::Send {
+        to_address: address.to_string(),
+        amount: coins(amount, DENOM),
+    })
+}
+
+#[test]
+fn unstakes_are_undelegated_once_per_epoch() {
+    let (mut deps, env) = setup();
+    for address in [USER, "other", "third"] {
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(address, &coins(1000, DENOM)),
+            ExecuteMsg::Stake {
+                pool_id: POOL_ID,
+                lock_type: constants::LOCKED_ONE_MONTH,
+                recipient: None,
+            },
+        )
+        .unwrap();
+    }
+    update_delegations(
+        &mut deps,
+        &[
+            delegation(VALIDATOR1, 1500, 0),
+            delegation(VALIDATOR2, 1500, 0),
+        ],
+    );
+
+    // the first unstake starts the epoch
+    let msgs = unstake_as(&mut deps, env.clone(), USER);
+    assert!(msgs.contains(&undelegate_msg(VALIDATOR1, 1000)));
+    assert_eq!(query_delegation(&deps).total_delegated, Uint128::new(2000));
+    update_delegations(
+        &mut deps,
+        &[
+            delegation(VALIDATOR1, 500, 0),
+            delegation(VALIDATOR2, 1500, 0),
+        ],
+    );
+
+    // the other unstakes of the epoch wait for the batch
+    let mut later = env.clone();
+    later.block.time = env.block.time.plus_seconds(1);
+    for address in ["other", "third"] {
+        let msgs = unstake_as(&mut deps, later.clone(), address);
+        assert!(!msgs
+            .iter()
+            .any(|msg| matches!(msg, CosmosMsg::Staking(StakingMsg::Undelegate { .. }))));
+        assert_eq!(
+            query_unbondings(&deps, address),
+            vec![UnbondingEntryResponse {
+                batch_id: 1,
+                amount: Uint128::new(1000),
+                release_time: None,
+            }]
+        );
+    }
+    let delegation_state = query_delegation(&deps);
+    assert_eq!(delegation_state.pending_undelegation, Uint128::new(2000));
+    assert_eq!(
+        delegation_state.next_undelegation_time,
+        env.block.time.seconds() + constants::UNDELEGATION_EPOCH_SECONDS
+    );
+    let err = withdraw_unbonded(&mut deps, later.clone(), "other").unwrap_err();
+    assert_eq!(err, ContractError::NotCreatedUnstaking {});
+
+    // the batch is undelegated once the epoch has passed, with one entry for each validator
+    let mut next_epoch = env.clone();
+    next_epoch.block.time = env
+        .block
+        .time
+        .plus_seconds(constants::UNDELEGATION_EPOCH_SECONDS);
+    let res = execute(
+        deps.as_mut(),
+        next_epoch.clone(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::HarvestDelegationRewards {},
+    )
+    .unwrap();
+    let msgs: Vec<CosmosMsg> = res.messages.into_iter().map(|msg| msg.msg).collect();
+    assert_eq!(
+        msgs[2..],
+        [
+            undelegate_msg(VALIDATOR1, 500),
+            undelegate_msg(VALIDATOR2, 1500)
+        ]
+    );
+    let delegation_state = query_delegation(&deps);
+    assert_eq!(delegation_state.total_delegated, Uint128::zero());
+    assert_eq!(delegation_state.pending_undelegation, Uint128::zero());
+    let release_time = next_epoch.block.time.seconds() + constants::UNBONDING_SECONDS;
+    assert_eq!(
+        query_unbondings(&deps, "other")[0].release_time,
+        Some(release_time)
+    );
+
+    // every staker withdraws the unbonded stake of its batch
+    let mut released = env.clone();
+    released.block.time = Timestamp::from_seconds(release_time);
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, coins(3000, DENOM));
+    for address in [USER, "other", "third"] {
+        let msgs = withdraw_unbonded(&mut deps, released.clone(), address).unwrap();
+        assert_eq!(msgs, vec![send_msg(address, 1000)]);
+        assert!(query_unbondings(&deps, address).is_empty());
+    }
+}
+
+#[test]
+fn slashed_stake_is_shared_by_the_unbondings() {
+    let (mut deps, env) = setup();
+    stake(&mut deps, env.clone(), 1000);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("other", &coins(1000, DENOM)),
+        ExecuteMsg::Stake {
+            pool_id: POOL_ID,
+            lock_type: constants::LOCKED_ONE_MONTH,
+            recipient: None,
+        },
+    )
+    .unwrap();
+
+    // the validators are slashed by 10%
+    update_delegations(
+        &mut deps,
+        &[
+            delegation(VALIDATOR1, 900, 0),
+            delegation(VALIDATOR2, 900, 0),
+        ],
+    );
+    let msgs = unstake_as(&mut deps, env.clone(), USER);
+    assert!(msgs.contains(&undelegate_msg(VALIDATOR1, 900)));
+    assert_eq!(query_unbondings(&deps, USER)[0].amount, Uint128::new(900));
+    assert_eq!(query_delegation(&deps).total_delegated, Uint128::new(1000));
+
+    // the last staker still gets its share out
+    update_delegations(&mut deps, &[delegation(VALIDATOR2, 900, 0)]);
+    let mut next_epoch = env.clone();
+    next_epoch.block.time = env
+        .block
+        .time
+        .plus_seconds(constants::UNDELEGATION_EPOCH_SECONDS);
+    let msgs = unstake_as(&mut deps, next_epoch.clone(), "other");
+    assert!(msgs.contains(&undelegate_msg(VALIDATOR2, 900)));
+    assert_eq!(query_delegation(&deps).total_delegated, Uint128::zero());
+
+    let mut released = next_epoch.clone();
+    released.block.time = next_epoch
+        .block
+        .time
+        .plus_seconds(constants::UNBONDING_SECONDS);
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, coins(1800, DENOM));
+    for address in [USER, "other"] {
+        let msgs = withdraw_unbonded(&mut deps, released.clone(), address).unwrap();
+        assert_eq!(msgs, vec![send_msg(address, 900)]);
+    }
+}
+
+#[test]
+fn unbonded_stake_and_unpaid_staking_reward_are_not_paid_as_reward() {
+    let (mut deps, env) = setup();
+    stake(&mut deps, env.clone(), 1000);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("other", &coins(1000, DENOM)),
+        ExecuteMsg::Stake {
+            pool_id: POOL_ID,
+            lock_type: constants::LOCKED_ONE_MONTH,
+            recipient: None,
+        },
+    )
+    .unwrap();
+    update_delegations(
+        &mut deps,
+        &[
+            delegation(VALIDATOR1, 1000, 0),
+            delegation(VALIDATOR2, 1000, 0),
+        ],
+    );
+    unstake_as(&mut deps, env.clone(), USER);
+
+    // the staking reward of the other staker is harvested
+    update_delegations(&mut deps, &[delegation(VALIDATOR2, 1000, 80)]);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::HarvestDelegationRewards {},
+    )
+    .unwrap();
+    update_delegations(&mut deps, &[delegation(VALIDATOR2, 1000, 0)]);
+
+    // the balance has the unbonded stake, the harvested reward and 50 of the reward
+    let mut released = env.clone();
+    released.block.time = env.block.time.plus_seconds(constants::UNBONDING_SECONDS);
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, coins(1000 + 80 + 50, DENOM));
+    let withdraw_reward = |amount| ExecuteMsg::WithdrawReward {
+        amount: Uint128::new(amount),
+    };
+    let err = execute(
+        deps.as_mut(),
+        released.clone(),
+        mock_info(OWNER, &[]),
+        withdraw_reward(51),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NotEnoughReward {});
+    execute(
+        deps.as_mut(),
+        released.clone(),
+        mock_info(OWNER, &[]),
+        withdraw_reward(50),
+    )
+    .unwrap();
+
+    // the staking reward can only be claimed from the harvested reward
+    let claim = ExecuteMsg::ClaimReward {
+        pool_id: POOL_ID,
+        distribution: RewardDistribution {
+            juno_reward: true,
+            charity: 0,
+            burn: 0,
+            artists: 0,
+        },
+    };
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, coins(1000 + 79, DENOM));
+    let err = execute(
+        deps.as_mut(),
+        released.clone(),
+        mock_info("other", &[]),
+        claim.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NotEnoughReward {});
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, coins(1000 + 80, DENOM));
+    let res = execute(
+        deps.as_mut(),
+        released.clone(),
+        mock_info("other", &[]),
+        claim,
+    )
+    .unwrap();
+    assert_eq!(res.messages.last().unwrap().msg, send_msg("other", 80));
+
+    let msgs = withdraw_unbonded(&mut deps, released, USER).unwrap();
+    assert_eq!(msgs, vec![send_msg(USER, 1000)]);
+}
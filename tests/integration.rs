@@ -0,0 +1,2929 @@
+use cosmwasm_std::{
+    coin, coins, to_json_binary, Addr, Coin, Deps, DepsMut, Empty, Env, Event, MessageInfo,
+    Response, StdResult, Timestamp, Uint128,
+};
+use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg, Denom};
+use cw721::{
+    AllNftInfoResponse, Approval, ApprovalResponse, ApprovalsResponse, OperatorsResponse,
+    OwnerOfResponse,
+};
+use cw_multi_test::error::{AnyError, AnyResult};
+use cw_multi_test::{App, AppResponse, Contract, ContractWrapper, Executor};
+use cw_storage_plus::Item;
+use cw_utils::{Expiration, Scheduled};
+use incentive::constants;
+use incentive::contract::default_tiers;
+use incentive::msg::{
+    AdminAction, BalanceResponse as HoleBalanceResponse, CapacityResponse, ConfigResponse,
+    ConstantsUpdate, ExecuteMsg, InstantiateMsg, PendingAdminAction, PendingAdminActionsResponse,
+    PendingRewardResponse, PoolResponse, Position, QueryMsg, ReceiveMsg, RewardDistribution,
+    RewardVestingResponse, StakerResponse, SwapAddressUpdate, Tier, UncheckedDenom,
+    UncheckedLpPool, WasmswapInfoResponse, WasmswapQueryMsg,
+};
+use incentive::ContractError;
+use sha2::{Digest, Sha256};
+
+const OWNER: &str = "owner";
+const USER: &str = "user";
+const CHARITY: &str = "charity";
+const BURN: &str = "burn";
+const ARTISTS: &str = "artists";
+const DENOM: &str = "ujuno";
+const POOL_ID: u64 = 0;
+
+const STAKE_AMOUNT: u128 = 1_000;
+// the one month tier gives 10% of the stake once the lock has passed
+const REWARD_AMOUNT: u128 = 100;
+const TIMELOCK: u64 = 48 * 60 * 60;
+
+fn hole_contract() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(
+        cw20_base::contract::execute,
+        cw20_base::contract::instantiate,
+        cw20_base::contract::query,
+    ))
+}
+
+fn incentive_contract() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(
+        incentive::contract::execute,
+        incentive::contract::instantiate,
+        incentive::contract::query,
+    ))
+}
+
+struct Suite {
+    app: App,
+    hole: Addr,
+    incentive: Addr,
+}
+
+impl Suite {
+    fn new() -> Self {
+        let mut app = App::new(|router, _, storage| {
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked(OWNER), coins(1_000_000, DENOM))
+                .unwrap();
+        });
+
+        let hole_id = app.store_code(hole_contract());
+        let hole = app
+            .instantiate_contract(
+                hole_id,
+                Addr::unchecked(OWNER),
+                &cw20_base::msg::InstantiateMsg {
+                    name: "Hole Token".to_string(),
+                    symbol: "HOLE".to_string(),
+                    decimals: 6,
+                    initial_balances: vec![
+                        Cw20Coin {
+                            address: OWNER.to_string(),
+                            amount: Uint128::new(1_000_000),
+                        },
+                        Cw20Coin {
+                            address: USER.to_string(),
+                            amount: Uint128::new(10_000),
+                        },
+                    ],
+                    mint: None,
+                    marketing: None,
+                },
+                &[],
+                "hole",
+                None,
+            )
+            .unwrap();
+
+        let incentive_id = app.store_code(incentive_contract());
+        let incentive = app
+            .instantiate_contract(
+                incentive_id,
+                Addr::unchecked(OWNER),
+                &InstantiateMsg {
+                    stake_token_address: hole.to_string(),
+                    reward_charity_address: CHARITY.to_string(),
+                    reward_burn_address: BURN.to_string(),
+                    reward_artists_address: ARTISTS.to_string(),
+                    reward_token_denom: DENOM.to_string(),
+                    reward_interval: constants::ONE_YEAR_SECONDS,
+                    owner: None,
+                    min_reward_interval: None,
+                    max_reward_interval: None,
+                },
+                &[],
+                "incentive",
+                None,
+            )
+            .unwrap();
+
+        // the amounts of the tests are below the default minimum stake
+        app.execute_contract(
+            Addr::unchecked(OWNER),
+            incentive.clone(),
+            &ExecuteMsg::UpdateStakeLimits {
+                min_stake_amount: Uint128::new(1),
+                max_positions_per_address: constants::DEFAULT_MAX_POSITIONS_PER_ADDRESS,
+            },
+            &[],
+        )
+        .unwrap();
+
+        // the reward pool of the contract
+        app.execute_contract(
+            Addr::unchecked(OWNER),
+            hole.clone(),
+            &Cw20ExecuteMsg::Transfer {
+                recipient: incentive.to_string(),
+                amount: Uint128::new(100_000),
+            },
+            &[],
+        )
+        .unwrap();
+        app.send_tokens(
+            Addr::unchecked(OWNER),
+            incentive.clone(),
+            &coins(100_000, DENOM),
+        )
+        .unwrap();
+
+        Suite {
+            app,
+            hole,
+            incentive,
+        }
+    }
+
+    fn stake(&mut self, sender: &str, amount: u128, lock_type: u64) -> AnyResult<AppResponse> {
+        self.stake_for(sender, None, amount, lock_type)
+    }
+
+    fn stake_for(
+        &mut self,
+        sender: &str,
+        recipient: Option<&str>,
+        amount: u128,
+        lock_type: u64,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(sender),
+            self.hole.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: self.incentive.to_string(),
+                amount: Uint128::new(amount),
+                msg: to_json_binary(&ReceiveMsg::Stake {
+                    pool_id: POOL_ID,
+                    lock_type,
+                    recipient: recipient.map(|recipient| recipient.to_string()),
+                })
+                .unwrap(),
+            },
+            &[],
+        )
+    }
+
+    fn fund_hole(&mut self, recipient: &str, amount: u128) {
+        self.app
+            .execute_contract(
+                Addr::unchecked(OWNER),
+                self.hole.clone(),
+                &Cw20ExecuteMsg::Transfer {
+                    recipient: recipient.to_string(),
+                    amount: Uint128::new(amount),
+                },
+                &[],
+            )
+            .unwrap();
+    }
+
+    fn execute(&mut self, sender: &str, msg: ExecuteMsg) -> AnyResult<AppResponse> {
+        self.app
+            .execute_contract(Addr::unchecked(sender), self.incentive.clone(), &msg, &[])
+    }
+
+    fn claim(&mut self, distribution: RewardDistribution) -> AnyResult<AppResponse> {
+        self.execute(
+            USER,
+            ExecuteMsg::ClaimReward {
+                pool_id: POOL_ID,
+                distribution,
+            },
+        )
+    }
+
+    fn advance_time(&mut self, seconds: u64) {
+        self.app.update_block(|block| {
+            block.time = block.time.plus_seconds(seconds);
+            block.height += seconds / 5;
+        });
+    }
+
+    fn hole_balance(&self, address: &str) -> u128 {
+        let res: BalanceResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(
+                self.hole.clone(),
+                &Cw20QueryMsg::Balance {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap();
+        res.balance.u128()
+    }
+
+    fn juno_balance(&self, address: &str) -> u128 {
+        self.app
+            .wrap()
+            .query_balance(address, DENOM)
+            .unwrap()
+            .amount
+            .u128()
+    }
+
+    fn pending_reward(&self) -> PendingRewardResponse {
+        self.app
+            .wrap()
+            .query_wasm_smart(
+                self.incentive.clone(),
+                &QueryMsg::PendingReward {
+                    pool_id: POOL_ID,
+                    address: USER.to_string(),
+                },
+            )
+            .unwrap()
+    }
+
+    fn staker(&self) -> StakerResponse {
+        self.staker_of(USER)
+    }
+
+    fn staker_of(&self, address: &str) -> StakerResponse {
+        self.app
+            .wrap()
+            .query_wasm_smart(
+                self.incentive.clone(),
+                &QueryMsg::Staker {
+                    pool_id: POOL_ID,
+                    address: address.to_string(),
+                },
+            )
+            .unwrap()
+    }
+
+    fn tier_staked(&self) -> Vec<u128> {
+        let capacity: CapacityResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(
+                self.incentive.clone(),
+                &QueryMsg::Capacity { pool_id: POOL_ID },
+            )
+            .unwrap();
+        capacity
+            .tiers
+            .iter()
+            .map(|tier| tier.staked.u128())
+            .collect()
+    }
+}
+
+fn wasm_event(attrs: &[(&str, &str)]) -> Event {
+    Event::new("wasm").add_attributes(attrs.iter().map(|(key, value)| (*key, *value)))
+}
+
+fn contract_error(err: AnyError) -> ContractError {
+    err.downcast().unwrap()
+}
+
+fn no_distribution(juno_reward: bool) -> RewardDistribution {
+    RewardDistribution {
+        juno_reward,
+        charity: 0,
+        burn: 0,
+        artists: 0,
+    }
+}
+
+#[test]
+fn stake_via_cw20_send() {
+    let mut suite = Suite::new();
+
+    let res = suite
+        .stake(USER, STAKE_AMOUNT, constants::LOCKED_ONE_MONTH)
+        .unwrap();
+    res.assert_event(&wasm_event(&[
+        ("action", "stake"),
+        ("pool_id", "0"),
+        ("position_id", "0"),
+        ("address", USER),
+        ("amount", "1000"),
+    ]));
+
+    assert_eq!(suite.hole_balance(USER), 10_000 - STAKE_AMOUNT);
+    assert_eq!(
+        suite.hole_balance(suite.incentive.as_str()),
+        100_000 + STAKE_AMOUNT
+    );
+
+    let staker = suite.staker();
+    assert_eq!(staker.positions.len(), 1);
+    assert_eq!(staker.positions[0].amount, Uint128::new(STAKE_AMOUNT));
+    assert_eq!(staker.positions[0].lock_type, constants::ONE_MONTH_SECONDS);
+    assert_eq!(staker.address, Addr::unchecked(USER));
+    assert_eq!(staker.total_staked, Uint128::new(STAKE_AMOUNT));
+    assert_eq!(staker.pending_reward, Uint128::zero());
+    assert_eq!(staker.unlockable, Uint128::zero());
+
+    let balance: HoleBalanceResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            suite.incentive.clone(),
+            &QueryMsg::GetHoleAmount {
+                address: suite.incentive.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(balance.balance, Uint128::new(100_000 + STAKE_AMOUNT));
+}
+
+#[test]
+fn stake_of_other_token_is_rejected() {
+    let mut suite = Suite::new();
+    let other_id = suite.app.store_code(hole_contract());
+    let other = suite
+        .app
+        .instantiate_contract(
+            other_id,
+            Addr::unchecked(OWNER),
+            &cw20_base::msg::InstantiateMsg {
+                name: "Other Token".to_string(),
+                symbol: "OTHER".to_string(),
+                decimals: 6,
+                initial_balances: vec![Cw20Coin {
+                    address: USER.to_string(),
+                    amount: Uint128::new(10_000),
+                }],
+                mint: None,
+                marketing: None,
+            },
+            &[],
+            "other",
+            None,
+        )
+        .unwrap();
+
+    let err = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            other,
+            &Cw20ExecuteMsg::Send {
+                contract: suite.incentive.to_string(),
+                amount: Uint128::new(STAKE_AMOUNT),
+                msg: to_json_binary(&ReceiveMsg::Stake {
+                    pool_id: POOL_ID,
+                    lock_type: constants::LOCKED_ONE_MONTH,
+                    recipient: None,
+                })
+                .unwrap(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::UnacceptableToken {});
+}
+
+#[test]
+fn reward_accrues_across_block_time_jumps() {
+    let mut suite = Suite::new();
+    suite
+        .stake(USER, STAKE_AMOUNT, constants::LOCKED_ONE_MONTH)
+        .unwrap();
+
+    // still locked
+    suite.advance_time(constants::ONE_MONTH_SECONDS - 1);
+    let pending = suite.pending_reward();
+    assert_eq!(pending.total_reward, Uint128::zero());
+    assert_eq!(pending.positions[0].unlock_in, 1);
+
+    suite.advance_time(1);
+    let staker = suite.staker();
+    assert_eq!(staker.pending_reward, Uint128::new(REWARD_AMOUNT));
+    assert_eq!(staker.unlockable, Uint128::new(STAKE_AMOUNT));
+    let pending = suite.pending_reward();
+    assert_eq!(pending.total_reward, Uint128::new(REWARD_AMOUNT));
+    assert_eq!(pending.native_reward, Uint128::new(REWARD_AMOUNT));
+    assert_eq!(pending.positions[0].unlock_in, 0);
+
+    // the reward is given once per lock period
+    suite.advance_time(constants::ONE_MONTH_SECONDS);
+    assert_eq!(
+        suite.pending_reward().total_reward,
+        Uint128::new(REWARD_AMOUNT)
+    );
+
+    // the lock starts again from the claim
+    suite.claim(no_distribution(false)).unwrap();
+    assert_eq!(suite.pending_reward().total_reward, Uint128::zero());
+    suite.advance_time(constants::ONE_MONTH_SECONDS);
+    assert_eq!(
+        suite.pending_reward().total_reward,
+        Uint128::new(REWARD_AMOUNT)
+    );
+}
+
+#[test]
+fn longer_tier_gives_higher_reward() {
+    let mut suite = Suite::new();
+    suite
+        .stake(USER, STAKE_AMOUNT, constants::LOCKED_ONE_YEAR)
+        .unwrap();
+
+    suite.advance_time(constants::ONE_YEAR_SECONDS);
+    assert_eq!(
+        suite.pending_reward().total_reward,
+        Uint128::new(STAKE_AMOUNT * constants::ONE_YEAR_APY as u128 / 100)
+    );
+}
+
+#[test]
+fn claim_with_every_reward_distribution() {
+    let shares: [(u64, u64, u64); 8] = [
+        (0, 0, 0),
+        (10, 0, 0),
+        (0, 20, 0),
+        (0, 0, 30),
+        (10, 20, 30),
+        (50, 50, 0),
+        (0, 0, 100),
+        (33, 33, 33),
+    ];
+
+    for juno_reward in [false, true] {
+        for (charity, burn, artists) in shares {
+            let mut suite = Suite::new();
+            suite
+                .stake(USER, STAKE_AMOUNT, constants::LOCKED_ONE_MONTH)
+                .unwrap();
+            suite.advance_time(constants::ONE_MONTH_SECONDS);
+
+            let res = suite
+                .claim(RewardDistribution {
+                    juno_reward,
+                    charity,
+                    burn,
+                    artists,
+                })
+                .unwrap();
+            res.assert_event(&wasm_event(&[
+                ("action", "claim_reward"),
+                ("address", USER),
+                ("reward_amount", "100"),
+            ]));
+
+            // the hole reward is always sent to the staker
+            assert_eq!(
+                suite.hole_balance(USER),
+                10_000 - STAKE_AMOUNT + REWARD_AMOUNT
+            );
+
+            let share = |percent: u64| {
+                if juno_reward {
+                    REWARD_AMOUNT * percent as u128 / 100
+                } else {
+                    0
+                }
+            };
+            assert_eq!(suite.juno_balance(CHARITY), share(charity));
+            assert_eq!(suite.juno_balance(BURN), share(burn));
+            assert_eq!(suite.juno_balance(ARTISTS), share(artists));
+            assert_eq!(
+                suite.juno_balance(USER),
+                share(100 - charity - burn - artists)
+            );
+        }
+    }
+}
+
+#[test]
+fn claim_before_lock_gives_nothing() {
+    let mut suite = Suite::new();
+    suite
+        .stake(USER, STAKE_AMOUNT, constants::LOCKED_ONE_MONTH)
+        .unwrap();
+    suite.advance_time(constants::ONE_MONTH_SECONDS / 2);
+
+    let res = suite.claim(no_distribution(true)).unwrap();
+    res.assert_event(&wasm_event(&[
+        ("action", "claim_reward"),
+        ("reward_amount", "0"),
+    ]));
+    assert_eq!(suite.hole_balance(USER), 10_000 - STAKE_AMOUNT);
+    assert_eq!(suite.juno_balance(USER), 0);
+}
+
+#[test]
+fn unstake_returns_principal() {
+    let mut suite = Suite::new();
+    suite
+        .stake(USER, STAKE_AMOUNT, constants::LOCKED_ONE_MONTH)
+        .unwrap();
+    suite.stake(USER, 500, constants::LOCKED_SIX_MONTH).unwrap();
+
+    let res = suite
+        .execute(USER, ExecuteMsg::Unstake { pool_id: POOL_ID })
+        .unwrap();
+    res.assert_event(&wasm_event(&[
+        ("action", "unstake"),
+        ("address", USER),
+        ("staked_amount", "1500"),
+    ]));
+
+    assert_eq!(suite.hole_balance(USER), 10_000);
+    assert_eq!(suite.hole_balance(suite.incentive.as_str()), 100_000);
+    assert!(suite.staker().positions.is_empty());
+}
+
+#[test]
+fn native_stake_is_returned_in_the_same_asset() {
+    let mut suite = Suite::new();
+    suite.app.init_modules(|router, _, storage| {
+        router
+            .bank
+            .init_balance(
+                storage,
+                &Addr::unchecked(USER),
+                vec![coin(10_000, DENOM), coin(10_000, "uother")],
+            )
+            .unwrap();
+    });
+    let hole = suite.hole.to_string();
+    suite
+        .execute(
+            OWNER,
+            ExecuteMsg::CreatePool {
+                stake_asset: UncheckedDenom::Native(DENOM.to_string()),
+                reward_asset: UncheckedDenom::Cw20(hole),
+                native_reward_asset: UncheckedDenom::Native(DENOM.to_string()),
+                tiers: default_tiers(),
+                lp: None,
+                delegated: false,
+                max_total_staked: None,
+            },
+        )
+        .unwrap();
+    let stake = |suite: &mut Suite, pool_id, funds: Vec<Coin>| {
+        suite.app.execute_contract(
+            Addr::unchecked(USER),
+            suite.incentive.clone(),
+            &ExecuteMsg::Stake {
+                pool_id,
+                lock_type: constants::LOCKED_ONE_MONTH,
+                recipient: None,
+            },
+            &funds,
+        )
+    };
+
+    // only the stake asset of the pool is accepted
+    let err = stake(&mut suite, 1, coins(STAKE_AMOUNT, "uother")).unwrap_err();
+    assert_eq!(contract_error(err), ContractError::UnacceptableToken {});
+    let err = stake(&mut suite, POOL_ID, coins(STAKE_AMOUNT, DENOM)).unwrap_err();
+    assert_eq!(contract_error(err), ContractError::UnacceptableToken {});
+
+    let res = stake(&mut suite, 1, coins(STAKE_AMOUNT, DENOM)).unwrap();
+    res.assert_event(&wasm_event(&[
+        ("action", "stake"),
+        ("pool_id", "1"),
+        ("asset", DENOM),
+        ("amount", "1000"),
+    ]));
+    assert_eq!(suite.juno_balance(USER), 10_000 - STAKE_AMOUNT);
+    assert_eq!(
+        suite.juno_balance(suite.incentive.as_str()),
+        100_000 + STAKE_AMOUNT
+    );
+
+    let res = suite
+        .execute(USER, ExecuteMsg::Unstake { pool_id: 1 })
+        .unwrap();
+    res.assert_event(&wasm_event(&[
+        ("action", "unstake"),
+        ("pool_id", "1"),
+        ("asset", DENOM),
+        ("staked_amount", "1000"),
+    ]));
+    assert_eq!(suite.juno_balance(USER), 10_000);
+    assert_eq!(suite.juno_balance(suite.incentive.as_str()), 100_000);
+    assert_eq!(suite.hole_balance(USER), 10_000);
+    assert_eq!(
+        suite
+            .app
+            .wrap()
+            .query_balance(USER, "uother")
+            .unwrap()
+            .amount
+            .u128(),
+        10_000
+    );
+}
+
+#[test]
+fn paused_contract_rejects_user_actions() {
+    let mut suite = Suite::new();
+    suite
+        .stake(USER, STAKE_AMOUNT, constants::LOCKED_ONE_MONTH)
+        .unwrap();
+
+    let err = suite
+        .execute(USER, ExecuteMsg::UpdateEnabled { enabled: false })
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::Unauthorized {});
+
+    let res = suite
+        .execute(OWNER, ExecuteMsg::UpdateEnabled { enabled: false })
+        .unwrap();
+    res.assert_event(&wasm_event(&[("action", "update_enabled")]));
+
+    let err = suite
+        .stake(USER, STAKE_AMOUNT, constants::LOCKED_ONE_MONTH)
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::Disabled {});
+    let err = suite.claim(no_distribution(true)).unwrap_err();
+    assert_eq!(contract_error(err), ContractError::Disabled {});
+    let err = suite
+        .execute(USER, ExecuteMsg::Unstake { pool_id: POOL_ID })
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::Disabled {});
+
+    suite
+        .execute(OWNER, ExecuteMsg::UpdateEnabled { enabled: true })
+        .unwrap();
+    suite
+        .execute(USER, ExecuteMsg::Unstake { pool_id: POOL_ID })
+        .unwrap();
+    assert_eq!(suite.hole_balance(USER), 10_000);
+}
+
+#[test]
+fn pause_flags_stop_only_their_own_action() {
+    let mut suite = Suite::new();
+    suite
+        .stake(USER, STAKE_AMOUNT, constants::LOCKED_ONE_MONTH)
+        .unwrap();
+    suite.advance_time(constants::ONE_MONTH_SECONDS);
+
+    let pause = |stake, claim, unstake| ExecuteMsg::UpdatePaused {
+        stake_paused: stake,
+        claim_paused: claim,
+        unstake_paused: unstake,
+    };
+    let err = suite
+        .execute(USER, pause(Some(true), None, None))
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::Unauthorized {});
+
+    // pausing the reward keeps the principal withdrawable
+    let res = suite
+        .execute(OWNER, pause(Some(true), Some(true), Some(false)))
+        .unwrap();
+    res.assert_event(
+        &Event::new("wasm-incentive_pause")
+            .add_attribute("paused_action", "stake")
+            .add_attribute("paused", "true"),
+    );
+    res.assert_event(
+        &Event::new("wasm-incentive_pause")
+            .add_attribute("paused_action", "claim")
+            .add_attribute("paused", "true"),
+    );
+    // unchanged flags emit no event
+    assert!(!res
+        .has_event(&Event::new("wasm-incentive_pause").add_attribute("paused_action", "unstake")));
+
+    let err = suite
+        .stake(USER, STAKE_AMOUNT, constants::LOCKED_ONE_MONTH)
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::StakePaused {});
+    let err = suite.claim(no_distribution(true)).unwrap_err();
+    assert_eq!(contract_error(err), ContractError::ClaimPaused {});
+
+    suite
+        .execute(OWNER, pause(Some(false), None, Some(true)))
+        .unwrap();
+    let err = suite
+        .execute(USER, ExecuteMsg::Unstake { pool_id: POOL_ID })
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::UnstakePaused {});
+    suite
+        .stake(USER, STAKE_AMOUNT, constants::LOCKED_ONE_MONTH)
+        .unwrap();
+
+    suite
+        .execute(OWNER, pause(None, Some(false), Some(false)))
+        .unwrap();
+    suite.claim(no_distribution(false)).unwrap();
+    suite
+        .execute(USER, ExecuteMsg::Unstake { pool_id: POOL_ID })
+        .unwrap();
+    assert_eq!(suite.hole_balance(USER), 10_000 + REWARD_AMOUNT);
+}
+
+#[test]
+fn emergency_withdraw_returns_principal_only() {
+    let mut suite = Suite::new();
+    suite
+        .stake(USER, STAKE_AMOUNT, constants::LOCKED_ONE_YEAR)
+        .unwrap();
+    suite.stake(USER, 500, constants::LOCKED_TWO_YEAR).unwrap();
+    suite.advance_time(constants::ONE_YEAR_SECONDS);
+
+    let err = suite
+        .execute(USER, ExecuteMsg::EmergencyWithdraw {})
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::NotEmergency {});
+    let err = suite
+        .execute(USER, ExecuteMsg::UpdateEmergency { emergency: true })
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::Unauthorized {});
+
+    // works while everything else is stopped
+    suite
+        .execute(OWNER, ExecuteMsg::UpdateEnabled { enabled: false })
+        .unwrap();
+    suite
+        .execute(
+            OWNER,
+            ExecuteMsg::UpdatePaused {
+                stake_paused: Some(true),
+                claim_paused: Some(true),
+                unstake_paused: Some(true),
+            },
+        )
+        .unwrap();
+    suite
+        .execute(OWNER, ExecuteMsg::UpdateEmergency { emergency: true })
+        .unwrap();
+
+    let res = suite
+        .execute(USER, ExecuteMsg::EmergencyWithdraw {})
+        .unwrap();
+    res.assert_event(&wasm_event(&[
+        ("action", "emergency_withdraw"),
+        ("address", USER),
+        ("positions", "2"),
+        ("amount", "1500"),
+    ]));
+
+    // the matured reward is forfeited
+    assert_eq!(suite.hole_balance(USER), 10_000);
+    assert!(suite.staker().positions.is_empty());
+
+    let err = suite
+        .execute(USER, ExecuteMsg::EmergencyWithdraw {})
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::NoStaked {});
+}
+
+#[test]
+fn timelocked_admin_actions() {
+    let mut suite = Suite::new();
+    let queue = |action| ExecuteMsg::QueueAdminAction { action };
+    let pending = |suite: &Suite| -> PendingAdminActionsResponse {
+        suite
+            .app
+            .wrap()
+            .query_wasm_smart(suite.incentive.clone(), &QueryMsg::PendingAdminActions {})
+            .unwrap()
+    };
+
+    let err = suite
+        .execute(
+            USER,
+            queue(AdminAction::UpdateAdminTimelock {
+                timelock_seconds: TIMELOCK,
+            }),
+        )
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::Unauthorized {});
+
+    // without a timelock the queued action can be executed at once
+    suite
+        .execute(
+            OWNER,
+            queue(AdminAction::UpdateAdminTimelock {
+                timelock_seconds: TIMELOCK,
+            }),
+        )
+        .unwrap();
+    let res = suite
+        .execute(OWNER, ExecuteMsg::ExecuteAdminAction { id: 0 })
+        .unwrap();
+    res.assert_event(&wasm_event(&[
+        ("action", "update_admin_timelock"),
+        ("timelock_seconds", "172800"),
+    ]));
+
+    let err = suite
+        .execute(
+            OWNER,
+            ExecuteMsg::WithdrawReward {
+                amount: Uint128::new(1_000),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::TimelockActive {});
+
+    suite
+        .execute(
+            OWNER,
+            queue(AdminAction::WithdrawReward {
+                amount: Uint128::new(1_000),
+            }),
+        )
+        .unwrap();
+    suite
+        .execute(
+            OWNER,
+            queue(AdminAction::UpdateOwner {
+                owner: USER.to_string(),
+            }),
+        )
+        .unwrap();
+
+    let now = suite.app.block_info().time.seconds();
+    let actions = pending(&suite);
+    assert_eq!(actions.timelock_seconds, TIMELOCK);
+    assert_eq!(
+        actions.actions,
+        vec![
+            PendingAdminAction {
+                id: 1,
+                action: AdminAction::WithdrawReward {
+                    amount: Uint128::new(1_000),
+                },
+                queued_at: now,
+                executable_at: now + TIMELOCK,
+            },
+            PendingAdminAction {
+                id: 2,
+                action: AdminAction::UpdateOwner {
+                    owner: USER.to_string(),
+                },
+                queued_at: now,
+                executable_at: now + TIMELOCK,
+            },
+        ]
+    );
+
+    let err = suite
+        .execute(OWNER, ExecuteMsg::ExecuteAdminAction { id: 1 })
+        .unwrap_err();
+    assert_eq!(
+        contract_error(err),
+        ContractError::TimelockNotExpired {
+            executable_at: now + TIMELOCK
+        }
+    );
+
+    // the owner change is cancelled before it lands
+    suite
+        .execute(OWNER, ExecuteMsg::CancelAdminAction { id: 2 })
+        .unwrap();
+
+    suite.advance_time(TIMELOCK);
+    let res = suite
+        .execute(OWNER, ExecuteMsg::ExecuteAdminAction { id: 1 })
+        .unwrap();
+    res.assert_event(&wasm_event(&[
+        ("action", "withdraw_reward"),
+        ("amount", "1000"),
+        ("id", "1"),
+    ]));
+    assert_eq!(suite.juno_balance(OWNER), 1_000_000 - 100_000 + 1_000);
+
+    let err = suite
+        .execute(OWNER, ExecuteMsg::ExecuteAdminAction { id: 2 })
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::AdminActionNotFound {});
+    let err = suite
+        .execute(OWNER, ExecuteMsg::ExecuteAdminAction { id: 1 })
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::AdminActionNotFound {});
+    assert!(pending(&suite).actions.is_empty());
+}
+
+#[test]
+fn queued_admin_actions_are_validated_and_tiers_are_timelocked() {
+    let mut suite = Suite::new();
+    let queue = |action| ExecuteMsg::QueueAdminAction { action };
+    suite
+        .execute(
+            OWNER,
+            queue(AdminAction::UpdateAdminTimelock {
+                timelock_seconds: TIMELOCK,
+            }),
+        )
+        .unwrap();
+    suite
+        .execute(OWNER, ExecuteMsg::ExecuteAdminAction { id: 0 })
+        .unwrap();
+
+    // an action which would fail is rejected when it is queued
+    let err = suite
+        .execute(
+            OWNER,
+            queue(AdminAction::UpdateOwner {
+                owner: String::new(),
+            }),
+        )
+        .unwrap_err();
+    assert!(matches!(contract_error(err), ContractError::Std(_)));
+
+    let constants_update = ConstantsUpdate {
+        reward_interval: None,
+        reward_charity_address: None,
+        reward_burn_address: None,
+        reward_artists_address: None,
+        reward_token_denom: None,
+        stake_token_address: None,
+        swap_address: None,
+    };
+    let err = suite
+        .execute(
+            OWNER,
+            queue(AdminAction::UpdateConstants(ConstantsUpdate {
+                reward_interval: Some(0),
+                ..constants_update.clone()
+            })),
+        )
+        .unwrap_err();
+    assert_eq!(
+        contract_error(err),
+        ContractError::InvalidRewardInterval {
+            min: constants::DEFAULT_MIN_REWARD_INTERVAL,
+            max: constants::DEFAULT_MAX_REWARD_INTERVAL,
+        }
+    );
+    let err = suite
+        .execute(
+            OWNER,
+            queue(AdminAction::UpdateConstants(ConstantsUpdate {
+                reward_burn_address: Some(String::new()),
+                ..constants_update.clone()
+            })),
+        )
+        .unwrap_err();
+    assert!(matches!(contract_error(err), ContractError::Std(_)));
+    for update in [
+        ConstantsUpdate {
+            reward_token_denom: Some(String::new()),
+            ..constants_update.clone()
+        },
+        // the default pool has no lp
+        ConstantsUpdate {
+            swap_address: Some(SwapAddressUpdate {
+                pool_id: POOL_ID,
+                swap_address: "swap".to_string(),
+            }),
+            ..constants_update.clone()
+        },
+    ] {
+        let err = suite
+            .execute(OWNER, queue(AdminAction::UpdateConstants(update)))
+            .unwrap_err();
+        assert_eq!(contract_error(err), ContractError::InvalidInput {});
+    }
+    suite
+        .stake(USER, STAKE_AMOUNT, constants::LOCKED_ONE_MONTH)
+        .unwrap();
+    let err = suite
+        .execute(
+            OWNER,
+            queue(AdminAction::UpdateConstants(ConstantsUpdate {
+                stake_token_address: Some("hole2".to_string()),
+                ..constants_update.clone()
+            })),
+        )
+        .unwrap_err();
+    assert_eq!(
+        contract_error(err),
+        ContractError::PoolHasStake { pool_id: POOL_ID }
+    );
+    for (pool_id, tiers) in [(1, default_tiers()), (POOL_ID, vec![])] {
+        let err = suite
+            .execute(
+                OWNER,
+                queue(AdminAction::UpdatePoolTiers { pool_id, tiers }),
+            )
+            .unwrap_err();
+        assert_eq!(contract_error(err), ContractError::InvalidInput {});
+    }
+    assert!(suite
+        .app
+        .wrap()
+        .query_wasm_smart::<PendingAdminActionsResponse>(
+            suite.incentive.clone(),
+            &QueryMsg::PendingAdminActions {},
+        )
+        .unwrap()
+        .actions
+        .is_empty());
+
+    // the tiers can not be changed at once, while the enable state can
+    let mut tiers = default_tiers();
+    tiers[constants::LOCKED_ONE_YEAR as usize].apy *= 2;
+    let err = suite
+        .execute(
+            OWNER,
+            ExecuteMsg::UpdatePool {
+                pool_id: POOL_ID,
+                tiers: Some(tiers.clone()),
+                enabled: None,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::TimelockActive {});
+    suite
+        .execute(
+            OWNER,
+            ExecuteMsg::UpdatePool {
+                pool_id: POOL_ID,
+                tiers: None,
+                enabled: Some(false),
+            },
+        )
+        .unwrap();
+
+    suite
+        .execute(
+            OWNER,
+            queue(AdminAction::UpdatePoolTiers {
+                pool_id: POOL_ID,
+                tiers: tiers.clone(),
+            }),
+        )
+        .unwrap();
+    suite.advance_time(TIMELOCK);
+    let res = suite
+        .execute(OWNER, ExecuteMsg::ExecuteAdminAction { id: 1 })
+        .unwrap();
+    res.assert_event(&wasm_event(&[
+        ("action", "update_pool"),
+        ("pool_id", "0"),
+        ("enabled", "false"),
+        ("id", "1"),
+    ]));
+    let pool: PoolResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            suite.incentive.clone(),
+            &QueryMsg::Pool { pool_id: POOL_ID },
+        )
+        .unwrap();
+    assert_eq!(pool.pool.tiers, tiers);
+}
+
+#[test]
+fn reward_vesting_and_validators_are_timelocked() {
+    let mut suite = Suite::new();
+    let queue = |action| ExecuteMsg::QueueAdminAction { action };
+    suite
+        .execute(
+            OWNER,
+            queue(AdminAction::UpdateAdminTimelock {
+                timelock_seconds: TIMELOCK,
+            }),
+        )
+        .unwrap();
+    suite
+        .execute(OWNER, ExecuteMsg::ExecuteAdminAction { id: 0 })
+        .unwrap();
+
+    for msg in [
+        ExecuteMsg::UpdateRewardVesting {
+            vesting_seconds: 1000,
+        },
+        ExecuteMsg::UpdateValidators { validators: vec![] },
+    ] {
+        let err = suite.execute(OWNER, msg).unwrap_err();
+        assert_eq!(contract_error(err), ContractError::TimelockActive {});
+    }
+    for action in [
+        AdminAction::UpdateRewardVesting {
+            vesting_seconds: constants::MAX_REWARD_VESTING_SECONDS + 1,
+        },
+        AdminAction::UpdateValidators {
+            validators: vec!["unknown".to_string()],
+        },
+    ] {
+        let err = suite.execute(OWNER, queue(action)).unwrap_err();
+        assert_eq!(contract_error(err), ContractError::InvalidInput {});
+    }
+
+    suite
+        .execute(
+            OWNER,
+            queue(AdminAction::UpdateRewardVesting {
+                vesting_seconds: constants::MAX_REWARD_VESTING_SECONDS,
+            }),
+        )
+        .unwrap();
+    suite.advance_time(TIMELOCK);
+    let res = suite
+        .execute(OWNER, ExecuteMsg::ExecuteAdminAction { id: 1 })
+        .unwrap();
+    res.assert_event(&wasm_event(&[
+        ("action", "update_reward_vesting"),
+        (
+            "vesting_seconds",
+            &constants::MAX_REWARD_VESTING_SECONDS.to_string(),
+        ),
+        ("id", "1"),
+    ]));
+    let config: ConfigResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(suite.incentive.clone(), &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(
+        config.reward_vesting_seconds,
+        constants::MAX_REWARD_VESTING_SECONDS
+    );
+}
+
+#[test]
+fn update_constants_is_validated() {
+    let mut suite = Suite::new();
+    let update = |reward_interval, charity: Option<&str>| {
+        ExecuteMsg::UpdateConstants(ConstantsUpdate {
+            reward_interval,
+            reward_charity_address: charity.map(str::to_string),
+            reward_burn_address: None,
+            reward_artists_address: None,
+            reward_token_denom: None,
+            stake_token_address: None,
+            swap_address: None,
+        })
+    };
+
+    let err = suite
+        .execute(USER, update(Some(constants::ONE_MONTH_SECONDS), None))
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::Unauthorized {});
+
+    for reward_interval in [0, constants::TWO_YEAR_SECONDS + 1, u64::MAX] {
+        let err = suite
+            .execute(OWNER, update(Some(reward_interval), None))
+            .unwrap_err();
+        assert_eq!(
+            contract_error(err),
+            ContractError::InvalidRewardInterval {
+                min: constants::DEFAULT_MIN_REWARD_INTERVAL,
+                max: constants::DEFAULT_MAX_REWARD_INTERVAL,
+            }
+        );
+    }
+
+    // an invalid address fails the whole update
+    let err = suite
+        .execute(OWNER, update(Some(constants::ONE_MONTH_SECONDS), Some("")))
+        .unwrap_err();
+    assert!(matches!(contract_error(err), ContractError::Std(_)));
+
+    let res = suite
+        .execute(
+            OWNER,
+            update(Some(constants::ONE_MONTH_SECONDS), Some("charity2")),
+        )
+        .unwrap();
+    res.assert_event(&wasm_event(&[
+        ("action", "update_constants"),
+        ("reward_interval", "2628000"),
+        ("reward_charity_address", "charity2"),
+    ]));
+
+    let config: ConfigResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(suite.incentive.clone(), &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.reward_interval, constants::ONE_MONTH_SECONDS);
+    assert_eq!(config.reward_charity_address, Addr::unchecked("charity2"));
+    assert_eq!(config.reward_burn_address, Addr::unchecked(BURN));
+
+    // the default pool follows the stake token and the reward denom, once it has no stake
+    let update_tokens = ExecuteMsg::UpdateConstants(ConstantsUpdate {
+        reward_interval: None,
+        reward_charity_address: None,
+        reward_burn_address: None,
+        reward_artists_address: None,
+        reward_token_denom: Some("uatom".to_string()),
+        stake_token_address: Some("hole2".to_string()),
+        swap_address: None,
+    });
+    suite
+        .stake(USER, STAKE_AMOUNT, constants::LOCKED_ONE_MONTH)
+        .unwrap();
+    let err = suite.execute(OWNER, update_tokens.clone()).unwrap_err();
+    assert_eq!(
+        contract_error(err),
+        ContractError::PoolHasStake { pool_id: POOL_ID }
+    );
+    suite
+        .execute(USER, ExecuteMsg::Unstake { pool_id: POOL_ID })
+        .unwrap();
+    suite.execute(OWNER, update_tokens).unwrap();
+    let pool: PoolResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            suite.incentive.clone(),
+            &QueryMsg::Pool { pool_id: POOL_ID },
+        )
+        .unwrap();
+    assert_eq!(pool.pool.stake_asset, Denom::Cw20(Addr::unchecked("hole2")));
+    assert_eq!(
+        pool.pool.reward_asset,
+        Denom::Cw20(Addr::unchecked("hole2"))
+    );
+    assert_eq!(
+        pool.pool.native_reward_asset,
+        Denom::Native("uatom".to_string())
+    );
+}
+
+#[test]
+fn instantiate_checks_reward_interval_bounds() {
+    let mut app = App::default();
+    let code_id = app.store_code(incentive_contract());
+    let instantiate = |app: &mut App, reward_interval, min, max| {
+        app.instantiate_contract(
+            code_id,
+            Addr::unchecked(OWNER),
+            &InstantiateMsg {
+                stake_token_address: "hole".to_string(),
+                reward_charity_address: CHARITY.to_string(),
+                reward_burn_address: BURN.to_string(),
+                reward_artists_address: ARTISTS.to_string(),
+                reward_token_denom: DENOM.to_string(),
+                reward_interval,
+                owner: None,
+                min_reward_interval: min,
+                max_reward_interval: max,
+            },
+            &[],
+            "incentive",
+            None,
+        )
+    };
+
+    let err = instantiate(&mut app, 0, None, None).unwrap_err();
+    assert_eq!(
+        contract_error(err),
+        ContractError::InvalidRewardInterval {
+            min: constants::DEFAULT_MIN_REWARD_INTERVAL,
+            max: constants::DEFAULT_MAX_REWARD_INTERVAL,
+        }
+    );
+    let err = instantiate(&mut app, 100, Some(0), Some(100)).unwrap_err();
+    assert_eq!(contract_error(err), ContractError::InvalidInput {});
+    let err = instantiate(&mut app, 100, Some(200), Some(100)).unwrap_err();
+    assert_eq!(contract_error(err), ContractError::InvalidInput {});
+    instantiate(&mut app, 100, Some(10), Some(100)).unwrap();
+}
+
+#[test]
+fn instantiate_validates_addresses_and_sets_owner() {
+    let mut app = App::default();
+    let code_id = app.store_code(incentive_contract());
+    let msg = |owner: Option<&str>, charity: &str| InstantiateMsg {
+        stake_token_address: "hole".to_string(),
+        reward_charity_address: charity.to_string(),
+        reward_burn_address: BURN.to_string(),
+        reward_artists_address: ARTISTS.to_string(),
+        reward_token_denom: DENOM.to_string(),
+        reward_interval: constants::ONE_YEAR_SECONDS,
+        owner: owner.map(str::to_string),
+        min_reward_interval: None,
+        max_reward_interval: None,
+    };
+
+    for (owner, charity) in [(None, "Charity"), (None, ""), (Some("DAO"), CHARITY)] {
+        let err = app
+            .instantiate_contract(
+                code_id,
+                Addr::unchecked(OWNER),
+                &msg(owner, charity),
+                &[],
+                "incentive",
+                None,
+            )
+            .unwrap_err();
+        assert!(matches!(contract_error(err), ContractError::Std(_)));
+    }
+
+    let incentive = app
+        .instantiate_contract(
+            code_id,
+            Addr::unchecked(OWNER),
+            &msg(Some("dao"), CHARITY),
+            &[],
+            "incentive",
+            None,
+        )
+        .unwrap();
+    let config: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(incentive.clone(), &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.owner, Addr::unchecked("dao"));
+
+    // the deployer is not the owner
+    let err = app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            incentive.clone(),
+            &ExecuteMsg::UpdateEnabled { enabled: false },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::Unauthorized {});
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked("dao"),
+            incentive,
+            &ExecuteMsg::UpdateOwner {
+                owner: "New Owner".to_string(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(contract_error(err), ContractError::Std(_)));
+}
+
+#[test]
+fn owner_withdraws_reward_and_stake_tokens() {
+    let mut suite = Suite::new();
+
+    let err = suite
+        .execute(
+            USER,
+            ExecuteMsg::WithdrawReward {
+                amount: Uint128::new(1_000),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::Unauthorized {});
+    let err = suite
+        .execute(
+            USER,
+            ExecuteMsg::WithdrawStake {
+                amount: Uint128::new(1_000),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::Unauthorized {});
+
+    let res = suite
+        .execute(
+            OWNER,
+            ExecuteMsg::WithdrawReward {
+                amount: Uint128::new(1_000),
+            },
+        )
+        .unwrap();
+    res.assert_event(&wasm_event(&[
+        ("action", "withdraw_reward"),
+        ("address", OWNER),
+        ("amount", "1000"),
+    ]));
+    assert_eq!(suite.juno_balance(OWNER), 1_000_000 - 100_000 + 1_000);
+    assert_eq!(suite.juno_balance(suite.incentive.as_str()), 99_000);
+
+    let res = suite
+        .execute(
+            OWNER,
+            ExecuteMsg::WithdrawStake {
+                amount: Uint128::new(2_000),
+            },
+        )
+        .unwrap();
+    res.assert_event(&wasm_event(&[
+        ("action", "withdraw_stake"),
+        ("address", OWNER),
+        ("amount", "2000"),
+    ]));
+    assert_eq!(suite.hole_balance(OWNER), 1_000_000 - 100_000 + 2_000);
+    assert_eq!(suite.hole_balance(suite.incentive.as_str()), 98_000);
+
+    let err = suite
+        .execute(
+            OWNER,
+            ExecuteMsg::WithdrawReward {
+                amount: Uint128::new(1_000_000),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::NotEnoughReward {});
+}
+
+#[test]
+fn update_pool_keeps_staked_tiers() {
+    let mut suite = Suite::new();
+    suite
+        .stake(USER, STAKE_AMOUNT, constants::LOCKED_SIX_MONTH)
+        .unwrap();
+    let update = |tiers: Vec<Tier>| ExecuteMsg::UpdatePool {
+        pool_id: POOL_ID,
+        tiers: Some(tiers),
+        enabled: None,
+    };
+
+    // the six month tier is staked
+    let mut tiers = default_tiers();
+    tiers.remove(constants::LOCKED_SIX_MONTH as usize);
+    let err = suite.execute(OWNER, update(tiers)).unwrap_err();
+    assert_eq!(
+        contract_error(err),
+        ContractError::TierInUse {
+            lock_seconds: constants::SIX_MONTH_SECONDS
+        }
+    );
+
+    // an unstaked tier can be removed and a staked one repriced
+    let mut tiers = default_tiers();
+    tiers.remove(constants::LOCKED_TWO_YEAR as usize);
+    tiers[constants::LOCKED_SIX_MONTH as usize].apy = 30;
+    suite.execute(OWNER, update(tiers)).unwrap();
+    suite.advance_time(constants::SIX_MONTH_SECONDS);
+    assert_eq!(
+        suite.pending_reward().total_reward,
+        Uint128::new(STAKE_AMOUNT * 30 / 100)
+    );
+
+    // once unstaked the tier can be removed
+    suite
+        .execute(USER, ExecuteMsg::Unstake { pool_id: POOL_ID })
+        .unwrap();
+    let mut tiers = default_tiers();
+    tiers.remove(constants::LOCKED_SIX_MONTH as usize);
+    suite.execute(OWNER, update(tiers)).unwrap();
+}
+
+#[test]
+fn pool_tiers_are_validated() {
+    let mut suite = Suite::new();
+    let hole = suite.hole.to_string();
+    let create = |tiers: Vec<Tier>| ExecuteMsg::CreatePool {
+        stake_asset: UncheckedDenom::Cw20(hole.clone()),
+        reward_asset: UncheckedDenom::Cw20(hole.clone()),
+        native_reward_asset: UncheckedDenom::Native(DENOM.to_string()),
+        tiers,
+        lp: None,
+        delegated: false,
+        max_total_staked: None,
+    };
+    let update = |tiers: Vec<Tier>| ExecuteMsg::UpdatePool {
+        pool_id: POOL_ID,
+        tiers: Some(tiers),
+        enabled: None,
+    };
+
+    let mut no_lock = default_tiers();
+    no_lock[0].lock_seconds = 0;
+    let mut duplicated = default_tiers();
+    duplicated[1].lock_seconds = duplicated[0].lock_seconds;
+    let mut decreasing = default_tiers();
+    decreasing.swap(1, 2);
+    let mut too_long = default_tiers();
+    too_long[3].lock_seconds = u64::MAX;
+    for tiers in [vec![], no_lock, duplicated, decreasing, too_long] {
+        let err = suite.execute(OWNER, create(tiers.clone())).unwrap_err();
+        assert_eq!(contract_error(err), ContractError::InvalidInput {});
+        let err = suite.execute(OWNER, update(tiers)).unwrap_err();
+        assert_eq!(contract_error(err), ContractError::InvalidInput {});
+    }
+
+    let mut longest = default_tiers();
+    longest[3].lock_seconds = constants::MAX_LOCK_SECONDS;
+    suite.execute(OWNER, create(longest.clone())).unwrap();
+    suite.execute(OWNER, update(longest)).unwrap();
+}
+
+#[test]
+fn disabled_pool_can_still_be_unstaked() {
+    let mut suite = Suite::new();
+    suite
+        .stake(USER, STAKE_AMOUNT, constants::LOCKED_ONE_MONTH)
+        .unwrap();
+    suite
+        .execute(
+            OWNER,
+            ExecuteMsg::UpdatePool {
+                pool_id: POOL_ID,
+                tiers: None,
+                enabled: Some(false),
+            },
+        )
+        .unwrap();
+
+    let err = suite
+        .stake(USER, STAKE_AMOUNT, constants::LOCKED_ONE_MONTH)
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::Disabled {});
+
+    suite
+        .execute(USER, ExecuteMsg::Unstake { pool_id: POOL_ID })
+        .unwrap();
+    assert_eq!(suite.hole_balance(USER), 10_000);
+    assert!(suite.staker().positions.is_empty());
+}
+
+// a wasmswap pool which only answers the info query, the reserves are set by the test
+const SWAP_INFO: Item<WasmswapInfoResponse> = Item::new("info");
+
+fn swap_contract() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(
+        |deps: DepsMut, _env: Env, _info: MessageInfo, msg: WasmswapInfoResponse| {
+            SWAP_INFO.save(deps.storage, &msg)?;
+            StdResult::Ok(Response::new())
+        },
+        |deps: DepsMut, _env: Env, _info: MessageInfo, msg: WasmswapInfoResponse| {
+            SWAP_INFO.save(deps.storage, &msg)?;
+            StdResult::Ok(Response::new())
+        },
+        |deps: Deps, _env: Env, _msg: WasmswapQueryMsg| {
+            to_json_binary(&SWAP_INFO.load(deps.storage)?)
+        },
+    ))
+}
+
+struct LpSuite {
+    suite: Suite,
+    lp: Addr,
+    swap: Addr,
+}
+
+const LP_POOL_ID: u64 = 1;
+
+impl LpSuite {
+    fn new() -> Self {
+        let mut suite = Suite::new();
+        let lp_id = suite.app.store_code(hole_contract());
+        let lp = suite
+            .app
+            .instantiate_contract(
+                lp_id,
+                Addr::unchecked(OWNER),
+                &cw20_base::msg::InstantiateMsg {
+                    name: "Hole Juno LP".to_string(),
+                    symbol: "HOLELP".to_string(),
+                    decimals: 6,
+                    initial_balances: vec![Cw20Coin {
+                        address: USER.to_string(),
+                        amount: Uint128::new(1_000),
+                    }],
+                    mint: None,
+                    marketing: None,
+                },
+                &[],
+                "lp",
+                None,
+            )
+            .unwrap();
+
+        let swap_id = suite.app.store_code(swap_contract());
+        let swap = suite
+            .app
+            .instantiate_contract(
+                swap_id,
+                Addr::unchecked(OWNER),
+                &Self::swap_info(&suite, &lp, 10_000, 20_000),
+                &[],
+                "swap",
+                None,
+            )
+            .unwrap();
+
+        LpSuite { suite, lp, swap }
+    }
+
+    fn swap_info(suite: &Suite, lp: &Addr, juno: u128, hole: u128) -> WasmswapInfoResponse {
+        WasmswapInfoResponse {
+            token1_reserve: Uint128::new(juno),
+            token1_denom: Denom::Native(DENOM.to_string()),
+            token2_reserve: Uint128::new(hole),
+            token2_denom: Denom::Cw20(suite.hole.clone()),
+            lp_token_supply: Uint128::new(10_000),
+            lp_token_address: lp.to_string(),
+        }
+    }
+
+    fn create_pool(
+        &mut self,
+        stake_asset: UncheckedDenom,
+        reward_asset: UncheckedDenom,
+    ) -> AnyResult<AppResponse> {
+        let swap = self.swap.clone();
+        self.suite.execute(
+            OWNER,
+            ExecuteMsg::CreatePool {
+                stake_asset,
+                reward_asset,
+                native_reward_asset: UncheckedDenom::Native(DENOM.to_string()),
+                tiers: default_tiers(),
+                lp: Some(UncheckedLpPool {
+                    swap_address: swap.to_string(),
+                    reward_on_underlying: true,
+                }),
+                delegated: false,
+                max_total_staked: None,
+            },
+        )
+    }
+
+    // moves the reserves of the swap like a trade would
+    fn set_reserves(&mut self, juno: u128, hole: u128) {
+        let msg = Self::swap_info(&self.suite, &self.lp, juno, hole);
+        self.suite
+            .app
+            .execute_contract(Addr::unchecked(OWNER), self.swap.clone(), &msg, &[])
+            .unwrap();
+    }
+
+    fn stake(&mut self, amount: u128) -> AnyResult<AppResponse> {
+        self.suite.app.execute_contract(
+            Addr::unchecked(USER),
+            self.lp.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: self.suite.incentive.to_string(),
+                amount: Uint128::new(amount),
+                msg: to_json_binary(&ReceiveMsg::Stake {
+                    pool_id: LP_POOL_ID,
+                    lock_type: constants::LOCKED_ONE_MONTH,
+                    recipient: None,
+                })
+                .unwrap(),
+            },
+            &[],
+        )
+    }
+
+    fn staker(&self) -> StakerResponse {
+        self.suite
+            .app
+            .wrap()
+            .query_wasm_smart(
+                self.suite.incentive.clone(),
+                &QueryMsg::Staker {
+                    pool_id: LP_POOL_ID,
+                    address: USER.to_string(),
+                },
+            )
+            .unwrap()
+    }
+}
+
+#[test]
+fn lp_pool_checks_the_swap() {
+    let mut lp = LpSuite::new();
+    let hole = UncheckedDenom::Cw20(lp.suite.hole.to_string());
+
+    // the addresses of the message are validated
+    lp.create_pool(UncheckedDenom::Cw20("Invalid".to_string()), hole.clone())
+        .unwrap_err();
+    let swap = std::mem::replace(&mut lp.swap, Addr::unchecked("Invalid"));
+    let lp_token = UncheckedDenom::Cw20(lp.lp.to_string());
+    lp.create_pool(lp_token.clone(), hole.clone()).unwrap_err();
+    lp.swap = swap;
+
+    // the stake asset must be the lp token of the swap
+    let err = lp.create_pool(hole.clone(), hole.clone()).unwrap_err();
+    assert_eq!(contract_error(err), ContractError::UnacceptableToken {});
+
+    // the reward on the underlying asset must be one of the pair
+    let err = lp
+        .create_pool(
+            lp_token.clone(),
+            UncheckedDenom::Native("uatom".to_string()),
+        )
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::UnacceptableToken {});
+
+    let res = lp.create_pool(lp_token, hole).unwrap();
+    res.assert_event(&wasm_event(&[("action", "create_pool"), ("pool_id", "1")]));
+}
+
+#[test]
+fn lp_pool_reward_is_fixed_at_stake_time() {
+    let mut lp = LpSuite::new();
+    let lp_token = UncheckedDenom::Cw20(lp.lp.to_string());
+    let hole = UncheckedDenom::Cw20(lp.suite.hole.to_string());
+    lp.create_pool(lp_token, hole).unwrap();
+
+    // 1_000 of 10_000 lp tokens is 2_000 hole of the reserve
+    lp.stake(1_000).unwrap();
+    let staker = lp.staker();
+    assert_eq!(staker.positions[0].amount, Uint128::new(1_000));
+    assert_eq!(staker.positions[0].reward_base, Uint128::new(2_000));
+    let underlying = staker.underlying.unwrap();
+    assert_eq!(underlying.token1_amount, Uint128::new(1_000));
+    assert_eq!(underlying.token2_amount, Uint128::new(2_000));
+
+    lp.suite.advance_time(constants::ONE_MONTH_SECONDS);
+    assert_eq!(lp.staker().pending_reward, Uint128::new(200));
+
+    // a trade moving hole into the swap before claiming does not inflate the reward
+    lp.set_reserves(1_000, 200_000);
+    let staker = lp.staker();
+    assert_eq!(staker.pending_reward, Uint128::new(200));
+    assert_eq!(
+        staker.underlying.unwrap().token2_amount,
+        Uint128::new(20_000)
+    );
+
+    lp.suite
+        .execute(
+            USER,
+            ExecuteMsg::ClaimReward {
+                pool_id: LP_POOL_ID,
+                distribution: no_distribution(false),
+            },
+        )
+        .unwrap();
+    assert_eq!(lp.suite.hole_balance(USER), 10_000 + 200);
+
+    // the lp tokens are returned on unstake
+    lp.suite
+        .execute(
+            USER,
+            ExecuteMsg::Unstake {
+                pool_id: LP_POOL_ID,
+            },
+        )
+        .unwrap();
+    assert!(lp.staker().positions.is_empty());
+}
+
+#[test]
+fn position_nft_approvals_and_operators() {
+    let mut suite = Suite::new();
+    suite
+        .stake(USER, STAKE_AMOUNT, constants::LOCKED_ONE_MONTH)
+        .unwrap();
+    let token_id = "0".to_string();
+    let transfer = |recipient: &str| ExecuteMsg::TransferNft {
+        recipient: recipient.to_string(),
+        token_id: "0".to_string(),
+    };
+    let approvals = |suite: &Suite, include_expired| -> ApprovalsResponse {
+        suite
+            .app
+            .wrap()
+            .query_wasm_smart(
+                suite.incentive.clone(),
+                &QueryMsg::Approvals {
+                    token_id: "0".to_string(),
+                    include_expired: Some(include_expired),
+                },
+            )
+            .unwrap()
+    };
+
+    let err = suite.execute("market", transfer("market")).unwrap_err();
+    assert_eq!(contract_error(err), ContractError::Unauthorized {});
+
+    let height = suite.app.block_info().height;
+    suite
+        .execute(
+            USER,
+            ExecuteMsg::Approve {
+                spender: "market".to_string(),
+                token_id: token_id.clone(),
+                expires: Some(Expiration::AtHeight(height + 10)),
+            },
+        )
+        .unwrap();
+    let approval: ApprovalResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            suite.incentive.clone(),
+            &QueryMsg::Approval {
+                token_id: token_id.clone(),
+                spender: "market".to_string(),
+                include_expired: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(approval.approval.expires, Expiration::AtHeight(height + 10));
+    suite
+        .app
+        .wrap()
+        .query_wasm_smart::<ApprovalResponse>(
+            suite.incentive.clone(),
+            &QueryMsg::Approval {
+                token_id: token_id.clone(),
+                spender: "other".to_string(),
+                include_expired: None,
+            },
+        )
+        .unwrap_err();
+
+    let info: AllNftInfoResponse<Position> = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            suite.incentive.clone(),
+            &QueryMsg::AllNftInfo {
+                token_id: token_id.clone(),
+                include_expired: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(info.access.owner, USER);
+    assert_eq!(info.access.approvals.len(), 1);
+    assert_eq!(info.info.extension.amount, Uint128::new(STAKE_AMOUNT));
+
+    // an expired approval is hidden and can not transfer
+    suite.advance_time(100);
+    assert!(approvals(&suite, false).approvals.is_empty());
+    assert_eq!(approvals(&suite, true).approvals.len(), 1);
+    let err = suite.execute("market", transfer("market")).unwrap_err();
+    assert_eq!(contract_error(err), ContractError::Unauthorized {});
+
+    suite
+        .execute(
+            USER,
+            ExecuteMsg::Revoke {
+                spender: "market".to_string(),
+                token_id: token_id.clone(),
+            },
+        )
+        .unwrap();
+    assert!(approvals(&suite, true).approvals.is_empty());
+
+    // an operator approves the market for the owner
+    suite
+        .execute(
+            USER,
+            ExecuteMsg::ApproveAll {
+                operator: "operator".to_string(),
+                expires: None,
+            },
+        )
+        .unwrap();
+    let operators: OperatorsResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            suite.incentive.clone(),
+            &QueryMsg::AllOperators {
+                owner: USER.to_string(),
+                include_expired: None,
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        operators.operators,
+        vec![Approval {
+            spender: "operator".to_string(),
+            expires: Expiration::Never {},
+        }]
+    );
+    suite
+        .execute(
+            "operator",
+            ExecuteMsg::Approve {
+                spender: "market".to_string(),
+                token_id: token_id.clone(),
+                expires: None,
+            },
+        )
+        .unwrap();
+
+    let res = suite.execute("market", transfer("buyer")).unwrap();
+    res.assert_event(&wasm_event(&[
+        ("action", "transfer_nft"),
+        ("sender", "market"),
+        ("recipient", "buyer"),
+        ("token_id", "0"),
+    ]));
+    let owner: OwnerOfResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            suite.incentive.clone(),
+            &QueryMsg::OwnerOf {
+                token_id,
+                include_expired: Some(true),
+            },
+        )
+        .unwrap();
+    assert_eq!(owner.owner, "buyer");
+    assert!(owner.approvals.is_empty());
+    assert!(suite.staker().positions.is_empty());
+
+    // the nft transfer is stopped with the contract like the position transfer
+    suite
+        .execute(OWNER, ExecuteMsg::UpdateEnabled { enabled: false })
+        .unwrap();
+    let err = suite.execute("buyer", transfer(USER)).unwrap_err();
+    assert_eq!(contract_error(err), ContractError::Disabled {});
+}
+
+#[test]
+fn only_allowlisted_contracts_stake_for_others() {
+    let mut suite = Suite::new();
+    suite.fund_hole("vesting", 10_000);
+    suite.fund_hole("attacker", 10_000);
+
+    // nobody can stake for another address by default
+    let err = suite
+        .stake_for("attacker", Some(USER), 1, constants::LOCKED_ONE_YEAR)
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::Unauthorized {});
+    let err = suite
+        .stake_for(
+            "vesting",
+            Some(USER),
+            STAKE_AMOUNT,
+            constants::LOCKED_ONE_YEAR,
+        )
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::Unauthorized {});
+    // naming yourself is the same as no recipient
+    suite
+        .stake_for(USER, Some(USER), STAKE_AMOUNT, constants::LOCKED_ONE_MONTH)
+        .unwrap();
+
+    let err = suite
+        .execute(
+            "attacker",
+            ExecuteMsg::UpdateStakeOnBehalfAllowlist {
+                allowlist: Some(vec!["attacker".to_string()]),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::Unauthorized {});
+    suite
+        .execute(
+            OWNER,
+            ExecuteMsg::UpdateStakeOnBehalfAllowlist {
+                allowlist: Some(vec!["vesting".to_string()]),
+            },
+        )
+        .unwrap();
+
+    let res = suite
+        .stake_for(
+            "vesting",
+            Some(USER),
+            STAKE_AMOUNT,
+            constants::LOCKED_ONE_YEAR,
+        )
+        .unwrap();
+    res.assert_event(&wasm_event(&[
+        ("action", "stake"),
+        ("position_id", "1"),
+        ("address", USER),
+    ]));
+    let staker = suite.staker();
+    assert_eq!(staker.positions.len(), 2);
+    assert_eq!(staker.positions[1].owner, USER);
+    assert_eq!(staker.total_staked, Uint128::new(2 * STAKE_AMOUNT));
+
+    let err = suite
+        .stake_for("attacker", Some(USER), 1, constants::LOCKED_ONE_YEAR)
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::Unauthorized {});
+
+    // closing the allowlist again stops the contract
+    suite
+        .execute(
+            OWNER,
+            ExecuteMsg::UpdateStakeOnBehalfAllowlist { allowlist: None },
+        )
+        .unwrap();
+    let err = suite
+        .stake_for(
+            "vesting",
+            Some(USER),
+            STAKE_AMOUNT,
+            constants::LOCKED_ONE_YEAR,
+        )
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::Unauthorized {});
+    assert_eq!(suite.hole_balance("vesting"), 10_000 - STAKE_AMOUNT);
+}
+
+#[test]
+fn transfer_position_keeps_lock_and_reward() {
+    let mut suite = Suite::new();
+    suite
+        .stake(USER, STAKE_AMOUNT, constants::LOCKED_ONE_MONTH)
+        .unwrap();
+    let staked = suite.staker().positions[0].clone();
+    suite.advance_time(constants::ONE_MONTH_SECONDS);
+
+    let transfer = |recipient: &str| ExecuteMsg::TransferPosition {
+        position_id: 0,
+        recipient: recipient.to_string(),
+    };
+    let err = suite.execute("wallet", transfer("other")).unwrap_err();
+    assert_eq!(contract_error(err), ContractError::Unauthorized {});
+    let err = suite.execute(USER, transfer(USER)).unwrap_err();
+    assert_eq!(contract_error(err), ContractError::InvalidInput {});
+
+    let res = suite.execute(USER, transfer("wallet")).unwrap();
+    res.assert_event(&wasm_event(&[
+        ("action", "transfer_position"),
+        ("position_id", "0"),
+        ("from", USER),
+        ("to", "wallet"),
+        ("amount", "1000"),
+        ("last_time", &staked.last_time.to_string()),
+    ]));
+    assert!(suite.staker().positions.is_empty());
+    let wallet = suite.staker_of("wallet");
+    assert_eq!(wallet.positions.len(), 1);
+    let moved = &wallet.positions[0];
+    assert_eq!(moved.owner, "wallet");
+    assert_eq!(moved.amount, staked.amount);
+    assert_eq!(moved.lock_type, staked.lock_type);
+    assert_eq!(moved.last_time, staked.last_time);
+    assert_eq!(wallet.pending_reward, Uint128::new(REWARD_AMOUNT));
+
+    // the new owner claims the reward and unstakes the principal
+    suite
+        .execute(
+            "wallet",
+            ExecuteMsg::ClaimReward {
+                pool_id: POOL_ID,
+                distribution: no_distribution(false),
+            },
+        )
+        .unwrap();
+    suite
+        .execute("wallet", ExecuteMsg::Unstake { pool_id: POOL_ID })
+        .unwrap();
+    assert_eq!(suite.hole_balance("wallet"), STAKE_AMOUNT + REWARD_AMOUNT);
+}
+
+#[test]
+fn received_positions_are_not_capped_and_unstaked_in_pages() {
+    let mut suite = Suite::new();
+    suite.fund_hole("market", 10_000);
+    suite
+        .execute(
+            OWNER,
+            ExecuteMsg::UpdateStakeLimits {
+                min_stake_amount: Uint128::new(1),
+                max_positions_per_address: 2,
+            },
+        )
+        .unwrap();
+
+    // the records sent to the user are not capped, like the listings held by a marketplace escrow
+    for position_id in 0..3u64 {
+        suite
+            .stake("market", 1, constants::LOCKED_TWO_YEAR)
+            .unwrap();
+        suite
+            .execute(
+                "market",
+                ExecuteMsg::TransferNft {
+                    recipient: USER.to_string(),
+                    token_id: position_id.to_string(),
+                },
+            )
+            .unwrap();
+    }
+
+    // the user still stakes up to its own cap
+    suite
+        .stake(USER, STAKE_AMOUNT, constants::LOCKED_ONE_MONTH)
+        .unwrap();
+    suite
+        .stake(USER, STAKE_AMOUNT, constants::LOCKED_ONE_MONTH)
+        .unwrap();
+    let err = suite
+        .stake(USER, STAKE_AMOUNT, constants::LOCKED_ONE_MONTH)
+        .unwrap_err();
+    assert_eq!(
+        contract_error(err),
+        ContractError::StakingRecordIndexOverflow {}
+    );
+    assert_eq!(suite.staker().positions.len(), 5);
+
+    // claim and unstake handle at most the cap of records at once, the oldest first
+    let res = suite.claim(no_distribution(false)).unwrap();
+    res.assert_event(&wasm_event(&[
+        ("action", "claim_reward"),
+        ("more_positions", "true"),
+    ]));
+    for (more, left) in [("true", 3), ("true", 1), ("false", 0)] {
+        let res = suite
+            .execute(USER, ExecuteMsg::Unstake { pool_id: POOL_ID })
+            .unwrap();
+        res.assert_event(&wasm_event(&[
+            ("action", "unstake"),
+            ("more_positions", more),
+        ]));
+        assert_eq!(suite.staker().positions.len(), left);
+    }
+    assert_eq!(suite.hole_balance(USER), 10_000 + 3);
+}
+
+#[test]
+fn extend_lock_settles_the_reward_and_restarts_the_lock() {
+    let mut suite = Suite::new();
+    suite
+        .stake(USER, STAKE_AMOUNT, constants::LOCKED_ONE_MONTH)
+        .unwrap();
+    let extend = |new_lock_type| ExecuteMsg::ExtendLock {
+        position_id: 0,
+        new_lock_type,
+    };
+
+    let err = suite
+        .execute("other", extend(constants::LOCKED_ONE_YEAR))
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::Unauthorized {});
+    let err = suite
+        .execute(USER, extend(constants::LOCKED_ONE_MONTH))
+        .unwrap_err();
+    assert_eq!(
+        contract_error(err),
+        ContractError::LockNotExtended {
+            current: constants::ONE_MONTH_SECONDS,
+            requested: constants::ONE_MONTH_SECONDS,
+        }
+    );
+
+    // the longer tier is full
+    let mut tiers = default_tiers();
+    tiers[constants::LOCKED_ONE_YEAR as usize].capacity = Some(Uint128::new(STAKE_AMOUNT - 1));
+    suite
+        .execute(
+            OWNER,
+            ExecuteMsg::UpdatePool {
+                pool_id: POOL_ID,
+                tiers: Some(tiers),
+                enabled: None,
+            },
+        )
+        .unwrap();
+    let err = suite
+        .execute(USER, extend(constants::LOCKED_ONE_YEAR))
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::CapacityExceeded {});
+
+    // the matured one month reward is kept while the six month lock starts
+    suite.advance_time(constants::ONE_MONTH_SECONDS);
+    let res = suite
+        .execute(USER, extend(constants::LOCKED_SIX_MONTH))
+        .unwrap();
+    res.assert_event(&wasm_event(&[
+        ("action", "extend_lock"),
+        ("position_id", "0"),
+        ("lock_type", &constants::SIX_MONTH_SECONDS.to_string()),
+        ("settled_reward", "100"),
+    ]));
+    let position = suite.staker().positions[0].clone();
+    assert_eq!(position.lock_type, constants::SIX_MONTH_SECONDS);
+    assert_eq!(position.last_time, suite.app.block_info().time.seconds());
+    assert_eq!(position.accrued, Uint128::new(REWARD_AMOUNT));
+    assert_eq!(
+        suite.pending_reward().total_reward,
+        Uint128::new(REWARD_AMOUNT)
+    );
+
+    assert_eq!(suite.tier_staked(), vec![0, STAKE_AMOUNT, 0, 0]);
+
+    // the settled reward is paid with the claim, the new lock is not matured yet
+    suite.claim(no_distribution(false)).unwrap();
+    assert_eq!(
+        suite.hole_balance(USER),
+        10_000 - STAKE_AMOUNT + REWARD_AMOUNT
+    );
+    assert!(suite.pending_reward().total_reward.is_zero());
+}
+
+#[test]
+fn merge_positions_upgrades_into_the_longest_lock() {
+    let mut suite = Suite::new();
+    suite
+        .stake(USER, STAKE_AMOUNT, constants::LOCKED_ONE_MONTH)
+        .unwrap();
+    suite.advance_time(constants::ONE_MONTH_SECONDS);
+    suite
+        .stake(USER, STAKE_AMOUNT / 2, constants::LOCKED_SIX_MONTH)
+        .unwrap();
+    suite.fund_hole("other", STAKE_AMOUNT);
+    suite
+        .stake("other", STAKE_AMOUNT, constants::LOCKED_ONE_MONTH)
+        .unwrap();
+    let merge = |position_ids: Vec<u64>| ExecuteMsg::MergePositions { position_ids };
+
+    let err = suite.execute(USER, merge(vec![0])).unwrap_err();
+    assert_eq!(contract_error(err), ContractError::InvalidInput {});
+    let err = suite.execute(USER, merge(vec![0, 1, 0])).unwrap_err();
+    assert_eq!(contract_error(err), ContractError::InvalidInput {});
+    let err = suite.execute(USER, merge(vec![0, 2])).unwrap_err();
+    assert_eq!(contract_error(err), ContractError::Unauthorized {});
+
+    // the one month record does not fit in the six month tier
+    let mut tiers = default_tiers();
+    tiers[constants::LOCKED_SIX_MONTH as usize].capacity = Some(Uint128::new(STAKE_AMOUNT));
+    let update = |tiers: Vec<Tier>| ExecuteMsg::UpdatePool {
+        pool_id: POOL_ID,
+        tiers: Some(tiers),
+        enabled: None,
+    };
+    suite.execute(OWNER, update(tiers)).unwrap();
+    let err = suite.execute(USER, merge(vec![0, 1])).unwrap_err();
+    assert_eq!(contract_error(err), ContractError::CapacityExceeded {});
+    suite.execute(OWNER, update(default_tiers())).unwrap();
+
+    // the matured one month record settles its reward and the six month lock starts now
+    let res = suite.execute(USER, merge(vec![0, 1])).unwrap();
+    res.assert_event(&wasm_event(&[
+        ("action", "merge_positions"),
+        ("position_id", "0"),
+        ("merged_position_ids", "0,1"),
+        ("amount", "1500"),
+        ("lock_type", &constants::SIX_MONTH_SECONDS.to_string()),
+        ("settled_reward", "100"),
+    ]));
+    let staker = suite.staker();
+    assert_eq!(staker.positions.len(), 1);
+    let merged = &staker.positions[0];
+    assert_eq!(merged.position_id, 0);
+    assert_eq!(merged.amount, Uint128::new(STAKE_AMOUNT * 3 / 2));
+    assert_eq!(merged.reward_base, Uint128::new(STAKE_AMOUNT * 3 / 2));
+    assert_eq!(merged.lock_type, constants::SIX_MONTH_SECONDS);
+    assert_eq!(merged.last_time, suite.app.block_info().time.seconds());
+    assert_eq!(merged.accrued, Uint128::new(REWARD_AMOUNT));
+    assert_eq!(staker.total_staked, Uint128::new(STAKE_AMOUNT * 3 / 2));
+    assert_eq!(
+        suite.tier_staked(),
+        vec![STAKE_AMOUNT, STAKE_AMOUNT * 3 / 2, 0, 0]
+    );
+    let num_tokens: cw721::NumTokensResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(suite.incentive.clone(), &QueryMsg::NumTokens {})
+        .unwrap();
+    assert_eq!(num_tokens.count, 2);
+}
+
+#[test]
+fn merge_positions_keeps_the_latest_lock_end() {
+    let mut suite = Suite::new();
+    suite
+        .stake(USER, STAKE_AMOUNT, constants::LOCKED_SIX_MONTH)
+        .unwrap();
+    suite.advance_time(constants::ONE_MONTH_SECONDS);
+    suite
+        .stake(USER, STAKE_AMOUNT, constants::LOCKED_SIX_MONTH)
+        .unwrap();
+    let second_start = suite.app.block_info().time.seconds();
+    suite.advance_time(constants::ONE_MONTH_SECONDS);
+
+    // no record is matured or upgraded, so the later lock end is kept
+    suite
+        .execute(
+            USER,
+            ExecuteMsg::MergePositions {
+                position_ids: vec![1, 0],
+            },
+        )
+        .unwrap();
+    let merged = suite.staker().positions[0].clone();
+    assert_eq!(merged.position_id, 1);
+    assert_eq!(merged.amount, Uint128::new(2 * STAKE_AMOUNT));
+    assert_eq!(merged.lock_type, constants::SIX_MONTH_SECONDS);
+    assert_eq!(merged.last_time, second_start);
+    assert!(merged.accrued.is_zero());
+
+    // the merged record matures with the later record
+    suite.advance_time(constants::SIX_MONTH_SECONDS - constants::ONE_MONTH_SECONDS - 1);
+    assert!(suite.pending_reward().total_reward.is_zero());
+    suite.advance_time(1);
+    assert!(!suite.pending_reward().total_reward.is_zero());
+}
+
+#[test]
+fn split_position_keeps_the_principal_and_lock() {
+    let mut suite = Suite::new();
+    suite
+        .stake(USER, STAKE_AMOUNT, constants::LOCKED_ONE_MONTH)
+        .unwrap();
+    let original = suite.staker().positions[0].clone();
+    let split = |amounts: Vec<u128>| ExecuteMsg::SplitPosition {
+        position_id: 0,
+        amounts: amounts.into_iter().map(Uint128::new).collect(),
+    };
+
+    let err = suite.execute("other", split(vec![100])).unwrap_err();
+    assert_eq!(contract_error(err), ContractError::Unauthorized {});
+    let err = suite.execute(USER, split(vec![])).unwrap_err();
+    assert_eq!(contract_error(err), ContractError::InvalidInput {});
+    let err = suite.execute(USER, split(vec![100, 0])).unwrap_err();
+    assert_eq!(contract_error(err), ContractError::InvalidInput {});
+    let err = suite
+        .execute(USER, split(vec![600, STAKE_AMOUNT - 500]))
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::NotEnoughStake {});
+    // nothing would be left in the record
+    let err = suite.execute(USER, split(vec![STAKE_AMOUNT])).unwrap_err();
+    assert_eq!(contract_error(err), ContractError::InvalidInput {});
+
+    let res = suite.execute(USER, split(vec![300, 200])).unwrap();
+    res.assert_event(&wasm_event(&[
+        ("action", "split_position"),
+        ("position_id", "0"),
+        ("new_position_ids", "1,2"),
+        ("remaining_amount", "500"),
+    ]));
+    let staker = suite.staker();
+    let amounts: Vec<u128> = staker
+        .positions
+        .iter()
+        .map(|position| position.amount.u128())
+        .collect();
+    assert_eq!(amounts, vec![500, 300, 200]);
+    assert_eq!(staker.total_staked, Uint128::new(STAKE_AMOUNT));
+    assert_eq!(suite.tier_staked(), vec![STAKE_AMOUNT, 0, 0, 0]);
+    for position in staker.positions.iter() {
+        assert_eq!(position.reward_base, position.amount);
+        assert_eq!(position.lock_type, original.lock_type);
+        assert_eq!(position.last_time, original.last_time);
+    }
+
+    // the split records together earn the reward of the original one
+    suite.advance_time(constants::ONE_MONTH_SECONDS);
+    assert_eq!(
+        suite.pending_reward().total_reward,
+        Uint128::new(REWARD_AMOUNT)
+    );
+}
+
+#[test]
+fn stake_limits_are_enforced() {
+    let mut suite = Suite::new();
+    let limits =
+        |min_stake_amount: u128, max_positions_per_address| ExecuteMsg::UpdateStakeLimits {
+            min_stake_amount: Uint128::new(min_stake_amount),
+            max_positions_per_address,
+        };
+
+    let err = suite.execute(USER, limits(100, 2)).unwrap_err();
+    assert_eq!(contract_error(err), ContractError::Unauthorized {});
+    let err = suite.execute(OWNER, limits(0, 2)).unwrap_err();
+    assert_eq!(contract_error(err), ContractError::InvalidInput {});
+    let err = suite.execute(OWNER, limits(100, 0)).unwrap_err();
+    assert_eq!(contract_error(err), ContractError::InvalidInput {});
+
+    let res = suite.execute(OWNER, limits(100, 2)).unwrap();
+    res.assert_event(&wasm_event(&[
+        ("action", "update_stake_limits"),
+        ("min_stake_amount", "100"),
+        ("max_positions_per_address", "2"),
+    ]));
+    let config: ConfigResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(suite.incentive.clone(), &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.min_stake_amount, Uint128::new(100));
+    assert_eq!(config.max_positions_per_address, 2);
+
+    let err = suite
+        .stake(USER, 99, constants::LOCKED_ONE_MONTH)
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::InvalidInput {});
+    suite.stake(USER, 100, constants::LOCKED_ONE_MONTH).unwrap();
+    suite.stake(USER, 100, constants::LOCKED_ONE_YEAR).unwrap();
+    let err = suite
+        .stake(USER, STAKE_AMOUNT, constants::LOCKED_ONE_MONTH)
+        .unwrap_err();
+    assert_eq!(
+        contract_error(err),
+        ContractError::StakingRecordIndexOverflow {}
+    );
+    assert_eq!(suite.hole_balance(USER), 10_000 - 200);
+
+    // unstaking frees the records again
+    suite
+        .execute(USER, ExecuteMsg::Unstake { pool_id: POOL_ID })
+        .unwrap();
+    suite
+        .stake(USER, STAKE_AMOUNT, constants::LOCKED_ONE_MONTH)
+        .unwrap();
+    assert_eq!(suite.staker().positions.len(), 1);
+}
+
+#[test]
+fn stake_over_the_capacity_is_refunded() {
+    let mut suite = Suite::new();
+    let capacity = |suite: &Suite| -> CapacityResponse {
+        suite
+            .app
+            .wrap()
+            .query_wasm_smart(
+                suite.incentive.clone(),
+                &QueryMsg::Capacity { pool_id: POOL_ID },
+            )
+            .unwrap()
+    };
+    let cap = |max_total_staked: Option<u128>| ExecuteMsg::UpdatePoolCap {
+        pool_id: POOL_ID,
+        max_total_staked: max_total_staked.map(Uint128::new),
+    };
+
+    let err = suite.execute(USER, cap(Some(1500))).unwrap_err();
+    assert_eq!(contract_error(err), ContractError::Unauthorized {});
+    suite.execute(OWNER, cap(Some(1500))).unwrap();
+
+    suite
+        .stake(USER, STAKE_AMOUNT, constants::LOCKED_ONE_MONTH)
+        .unwrap();
+    assert_eq!(capacity(&suite).remaining, Some(Uint128::new(500)));
+
+    // only the remaining capacity of the pool is staked
+    let res = suite
+        .stake(USER, STAKE_AMOUNT, constants::LOCKED_ONE_MONTH)
+        .unwrap();
+    res.assert_event(&wasm_event(&[
+        ("action", "stake"),
+        ("amount", "500"),
+        ("refund_amount", "500"),
+    ]));
+    assert_eq!(suite.hole_balance(USER), 10_000 - 1500);
+    let full = capacity(&suite);
+    assert_eq!(full.total_staked, Uint128::new(1500));
+    assert_eq!(full.remaining, Some(Uint128::zero()));
+    let err = suite
+        .stake(USER, STAKE_AMOUNT, constants::LOCKED_ONE_MONTH)
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::CapacityExceeded {});
+
+    // the capacity of the tier applies without the pool cap
+    suite.execute(OWNER, cap(None)).unwrap();
+    let mut tiers = default_tiers();
+    tiers[constants::LOCKED_TWO_YEAR as usize].capacity = Some(Uint128::new(300));
+    suite
+        .execute(
+            OWNER,
+            ExecuteMsg::UpdatePool {
+                pool_id: POOL_ID,
+                tiers: Some(tiers),
+                enabled: None,
+            },
+        )
+        .unwrap();
+    let res = suite
+        .stake(USER, STAKE_AMOUNT, constants::LOCKED_TWO_YEAR)
+        .unwrap();
+    res.assert_event(&wasm_event(&[
+        ("action", "stake"),
+        ("amount", "300"),
+        ("refund_amount", "700"),
+    ]));
+    assert_eq!(suite.hole_balance(USER), 10_000 - 1800);
+
+    let capacity = capacity(&suite);
+    assert_eq!(capacity.max_total_staked, None);
+    assert_eq!(capacity.remaining, None);
+    let two_year = &capacity.tiers[constants::LOCKED_TWO_YEAR as usize];
+    assert_eq!(two_year.staked, Uint128::new(300));
+    assert_eq!(two_year.remaining, Some(Uint128::zero()));
+    assert_eq!(capacity.tiers[0].remaining, None);
+    let err = suite
+        .stake(USER, STAKE_AMOUNT, constants::LOCKED_TWO_YEAR)
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::CapacityExceeded {});
+}
+
+#[test]
+fn hole_reward_is_not_paid_from_the_stake_or_the_vesting() {
+    let mut suite = Suite::new();
+    suite.fund_hole("other", STAKE_AMOUNT);
+    suite
+        .execute(
+            OWNER,
+            ExecuteMsg::UpdateRewardVesting {
+                vesting_seconds: 1000,
+            },
+        )
+        .unwrap();
+    suite
+        .stake(USER, STAKE_AMOUNT, constants::LOCKED_ONE_MONTH)
+        .unwrap();
+    suite
+        .stake("other", STAKE_AMOUNT, constants::LOCKED_ONE_MONTH)
+        .unwrap();
+
+    // only one reward is left besides the stake
+    suite
+        .execute(
+            OWNER,
+            ExecuteMsg::WithdrawStake {
+                amount: Uint128::new(100_000 - REWARD_AMOUNT),
+            },
+        )
+        .unwrap();
+    suite.advance_time(constants::ONE_MONTH_SECONDS);
+    suite.claim(no_distribution(false)).unwrap();
+
+    // the reward vesting for the user is kept too
+    let err = suite
+        .execute(
+            "other",
+            ExecuteMsg::ClaimReward {
+                pool_id: POOL_ID,
+                distribution: no_distribution(false),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::NotEnoughReward {});
+
+    suite
+        .execute("other", ExecuteMsg::Unstake { pool_id: POOL_ID })
+        .unwrap();
+    assert_eq!(suite.hole_balance("other"), STAKE_AMOUNT);
+    suite
+        .execute(USER, ExecuteMsg::Unstake { pool_id: POOL_ID })
+        .unwrap();
+    suite.advance_time(1000);
+    suite.execute(USER, ExecuteMsg::WithdrawVested {}).unwrap();
+    assert_eq!(suite.hole_balance(USER), 10_000 + REWARD_AMOUNT);
+}
+
+#[test]
+fn claimed_reward_vests_linearly() {
+    let mut suite = Suite::new();
+    let err = suite
+        .execute(
+            USER,
+            ExecuteMsg::UpdateRewardVesting {
+                vesting_seconds: 1000,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::Unauthorized {});
+    let err = suite
+        .execute(
+            OWNER,
+            ExecuteMsg::UpdateRewardVesting {
+                vesting_seconds: constants::MAX_REWARD_VESTING_SECONDS + 1,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::InvalidInput {});
+    suite
+        .execute(
+            OWNER,
+            ExecuteMsg::UpdateRewardVesting {
+                vesting_seconds: 1000,
+            },
+        )
+        .unwrap();
+
+    suite
+        .stake(USER, STAKE_AMOUNT, constants::LOCKED_ONE_MONTH)
+        .unwrap();
+    suite.advance_time(constants::ONE_MONTH_SECONDS);
+    suite.claim(no_distribution(false)).unwrap();
+    // nothing is sent at claim
+    assert_eq!(suite.hole_balance(USER), 10_000 - STAKE_AMOUNT);
+
+    let vesting = |suite: &Suite| -> RewardVestingResponse {
+        suite
+            .app
+            .wrap()
+            .query_wasm_smart(
+                suite.incentive.clone(),
+                &QueryMsg::RewardVesting {
+                    address: USER.to_string(),
+                },
+            )
+            .unwrap()
+    };
+    let entry = vesting(&suite).vestings[0].clone();
+    assert_eq!(entry.amount, Uint128::new(REWARD_AMOUNT));
+    assert_eq!(entry.vested, Uint128::zero());
+    assert_eq!(entry.unvested, Uint128::new(REWARD_AMOUNT));
+    assert_eq!(entry.end_time, entry.start_time + 1000);
+    let err = suite
+        .execute(USER, ExecuteMsg::WithdrawVested {})
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::NoReward {});
+
+    suite.advance_time(250);
+    let entry = vesting(&suite).vestings[0].clone();
+    assert_eq!(entry.vested, Uint128::new(25));
+    assert_eq!(entry.unvested, Uint128::new(75));
+    let res = suite.execute(USER, ExecuteMsg::WithdrawVested {}).unwrap();
+    res.assert_event(&wasm_event(&[
+        ("action", "withdraw_vested"),
+        ("address", USER),
+        ("amount", "25"),
+    ]));
+    assert_eq!(suite.hole_balance(USER), 10_000 - STAKE_AMOUNT + 25);
+    assert_eq!(vesting(&suite).vestings[0].withdrawn, Uint128::new(25));
+
+    // the rest is withdrawn after the end and the entry is removed
+    suite.advance_time(1000);
+    suite.execute(USER, ExecuteMsg::WithdrawVested {}).unwrap();
+    assert_eq!(
+        suite.hole_balance(USER),
+        10_000 - STAKE_AMOUNT + REWARD_AMOUNT
+    );
+    assert!(vesting(&suite).vestings.is_empty());
+    let err = suite
+        .execute(USER, ExecuteMsg::WithdrawVested {})
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::NoReward {});
+}
+
+fn leaf(address: &str, amount: u128) -> [u8; 32] {
+    Sha256::digest(format!("{}{}", address, amount).as_bytes()).into()
+}
+
+fn parent(left: [u8; 32], right: [u8; 32]) -> [u8; 32] {
+    let mut hashes = [left, right];
+    hashes.sort_unstable();
+    Sha256::digest(hashes.concat()).into()
+}
+
+#[test]
+fn airdrop_is_claimed_from_the_deposit_of_the_stage() {
+    let mut suite = Suite::new();
+    let user_leaf = leaf(USER, 500);
+    let friend_leaf = leaf("friend", 2000);
+    let merkle_root = hex::encode(parent(user_leaf, friend_leaf));
+    let now = suite.app.block_info().time.seconds();
+    let register = ReceiveMsg::RegisterMerkleRoot {
+        merkle_root: merkle_root.clone(),
+        start: Some(Scheduled::AtTime(Timestamp::from_seconds(now + 100))),
+        expiration: Some(Expiration::AtTime(Timestamp::from_seconds(now + 1000))),
+        pool_id: POOL_ID,
+    };
+    let deposit = |suite: &mut Suite, sender: &str| {
+        suite.app.execute_contract(
+            Addr::unchecked(sender),
+            suite.hole.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: suite.incentive.to_string(),
+                amount: Uint128::new(600),
+                msg: to_json_binary(&register).unwrap(),
+            },
+            &[],
+        )
+    };
+
+    // the stage is deposited in the stake asset of the pool by the owner
+    suite.fund_hole("attacker", 1000);
+    let err = deposit(&mut suite, "attacker").unwrap_err();
+    assert_eq!(contract_error(err), ContractError::Unauthorized {});
+    let err = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.incentive.clone(),
+            &ExecuteMsg::RegisterMerkleRoot {
+                merkle_root: merkle_root.clone(),
+                start: None,
+                expiration: None,
+                pool_id: POOL_ID,
+            },
+            &coins(600, DENOM),
+        )
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::UnacceptableToken {});
+
+    let contract_balance = suite.hole_balance(suite.incentive.as_str());
+    let res = deposit(&mut suite, OWNER).unwrap();
+    res.assert_event(&wasm_event(&[
+        ("action", "register_merkle_root"),
+        ("stage", "1"),
+        ("total_amount", "600"),
+    ]));
+    assert_eq!(
+        suite.hole_balance(suite.incentive.as_str()),
+        contract_balance + 600
+    );
+
+    let claim = |amount: u128, proof: [u8; 32]| ExecuteMsg::ClaimAirdrop {
+        stage: 1,
+        amount: Uint128::new(amount),
+        proof: vec![hex::encode(proof)],
+        lock_type: constants::LOCKED_ONE_YEAR,
+    };
+    let err = suite.execute(USER, claim(500, friend_leaf)).unwrap_err();
+    assert!(matches!(
+        contract_error(err),
+        ContractError::StageNotBegun { stage: 1, .. }
+    ));
+    suite.advance_time(100);
+    let err = suite.execute(USER, claim(600, friend_leaf)).unwrap_err();
+    assert_eq!(contract_error(err), ContractError::VerificationFailed {});
+    let err = suite.execute(USER, claim(500, user_leaf)).unwrap_err();
+    assert_eq!(contract_error(err), ContractError::VerificationFailed {});
+
+    // the claim is not partially staked and refunded
+    suite
+        .execute(
+            OWNER,
+            ExecuteMsg::UpdatePoolCap {
+                pool_id: POOL_ID,
+                max_total_staked: Some(Uint128::new(400)),
+            },
+        )
+        .unwrap();
+    let err = suite.execute(USER, claim(500, friend_leaf)).unwrap_err();
+    assert_eq!(contract_error(err), ContractError::CapacityExceeded {});
+    suite
+        .execute(
+            OWNER,
+            ExecuteMsg::UpdatePoolCap {
+                pool_id: POOL_ID,
+                max_total_staked: None,
+            },
+        )
+        .unwrap();
+
+    // the stake pause and limits do not stop the claim
+    suite
+        .execute(
+            OWNER,
+            ExecuteMsg::UpdateStakeLimits {
+                min_stake_amount: Uint128::new(STAKE_AMOUNT),
+                max_positions_per_address: 1,
+            },
+        )
+        .unwrap();
+    suite
+        .stake(USER, STAKE_AMOUNT, constants::LOCKED_ONE_MONTH)
+        .unwrap();
+    suite
+        .execute(
+            OWNER,
+            ExecuteMsg::UpdatePaused {
+                stake_paused: Some(true),
+                claim_paused: None,
+                unstake_paused: None,
+            },
+        )
+        .unwrap();
+    let res = suite.execute(USER, claim(500, friend_leaf)).unwrap();
+    res.assert_event(&wasm_event(&[
+        ("action", "claim_airdrop"),
+        ("stage", "1"),
+        ("position_id", "1"),
+        ("address", USER),
+        ("claimed_amount", "500"),
+        ("lock_type", &constants::ONE_YEAR_SECONDS.to_string()),
+    ]));
+    let staker = suite.staker();
+    assert_eq!(staker.positions.len(), 2);
+    assert_eq!(staker.positions[1].amount, Uint128::new(500));
+    assert_eq!(staker.positions[1].lock_type, constants::ONE_YEAR_SECONDS);
+    assert_eq!(suite.hole_balance(USER), 10_000 - STAKE_AMOUNT);
+
+    let err = suite.execute(USER, claim(500, friend_leaf)).unwrap_err();
+    assert_eq!(contract_error(err), ContractError::Claimed {});
+
+    // only 100 of the deposit is left for the friend
+    let err = suite.execute("friend", claim(2000, user_leaf)).unwrap_err();
+    assert_eq!(
+        contract_error(err),
+        ContractError::StageExhausted { stage: 1 }
+    );
+    let withdraw = ExecuteMsg::WithdrawAirdropRemainder { stage: 1 };
+    let err = suite.execute(OWNER, withdraw.clone()).unwrap_err();
+    assert_eq!(
+        contract_error(err),
+        ContractError::StageNotExpired { stage: 1 }
+    );
+    suite.advance_time(1000);
+    let err = suite.execute("friend", claim(2000, user_leaf)).unwrap_err();
+    assert!(matches!(
+        contract_error(err),
+        ContractError::StageExpired { stage: 1, .. }
+    ));
+
+    // the owner takes back the unclaimed deposit once
+    let err = suite.execute(USER, withdraw.clone()).unwrap_err();
+    assert_eq!(contract_error(err), ContractError::Unauthorized {});
+    let owner_balance = suite.hole_balance(OWNER);
+    let res = suite.execute(OWNER, withdraw.clone()).unwrap();
+    res.assert_event(&wasm_event(&[
+        ("action", "withdraw_airdrop_remainder"),
+        ("stage", "1"),
+        ("amount", "100"),
+    ]));
+    assert_eq!(suite.hole_balance(OWNER), owner_balance + 100);
+    let err = suite.execute(OWNER, withdraw).unwrap_err();
+    assert_eq!(
+        contract_error(err),
+        ContractError::StageExhausted { stage: 1 }
+    );
+}
+
+#[test]
+fn airdrop_stages_are_limited() {
+    let mut suite = Suite::new();
+    let register = |suite: &mut Suite| {
+        suite.app.execute_contract(
+            Addr::unchecked(OWNER),
+            suite.hole.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: suite.incentive.to_string(),
+                amount: Uint128::new(1),
+                msg: to_json_binary(&ReceiveMsg::RegisterMerkleRoot {
+                    merkle_root: hex::encode(leaf(USER, 1)),
+                    start: None,
+                    expiration: None,
+                    pool_id: POOL_ID,
+                })
+                .unwrap(),
+            },
+            &[],
+        )
+    };
+    for _ in 0..u8::MAX {
+        register(&mut suite).unwrap();
+    }
+    let err = register(&mut suite).unwrap_err();
+    assert_eq!(contract_error(err), ContractError::TooManyStages {});
+}
+
+#[test]
+fn lp_pool_swap_is_updated() {
+    let mut lp = LpSuite::new();
+    let lp_token = UncheckedDenom::Cw20(lp.lp.to_string());
+    lp.create_pool(lp_token, UncheckedDenom::Cw20(lp.suite.hole.to_string()))
+        .unwrap();
+    let swap_id = lp.suite.app.store_code(swap_contract());
+    let new_swap = |lp: &mut LpSuite, lp_token: Addr| {
+        let info = LpSuite::swap_info(&lp.suite, &lp_token, 10_000, 40_000);
+        lp.suite
+            .app
+            .instantiate_contract(swap_id, Addr::unchecked(OWNER), &info, &[], "swap", None)
+            .unwrap()
+    };
+    let update = |pool_id, swap_address: &Addr| {
+        ExecuteMsg::UpdateConstants(ConstantsUpdate {
+            reward_interval: None,
+            reward_charity_address: None,
+            reward_burn_address: None,
+            reward_artists_address: None,
+            reward_token_denom: None,
+            stake_token_address: None,
+            swap_address: Some(SwapAddressUpdate {
+                pool_id,
+                swap_address: swap_address.to_string(),
+            }),
+        })
+    };
+
+    let hole = lp.suite.hole.clone();
+    let other_swap = new_swap(&mut lp, hole);
+    let err = lp
+        .suite
+        .execute(OWNER, update(LP_POOL_ID, &other_swap))
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::UnacceptableToken {});
+
+    let lp_addr = lp.lp.clone();
+    let swap = new_swap(&mut lp, lp_addr);
+    let err = lp
+        .suite
+        .execute(USER, update(LP_POOL_ID, &swap))
+        .unwrap_err();
+    assert_eq!(contract_error(err), ContractError::Unauthorized {});
+    let err = lp.suite.execute(OWNER, update(POOL_ID, &swap)).unwrap_err();
+    assert_eq!(contract_error(err), ContractError::InvalidInput {});
+
+    let res = lp.suite.execute(OWNER, update(LP_POOL_ID, &swap)).unwrap();
+    res.assert_event(&wasm_event(&[
+        ("action", "update_constants"),
+        ("pool_id", "1"),
+        ("swap_address", swap.as_str()),
+    ]));
+
+    // the new stake is priced with the reserves of the new swap
+    lp.stake(1_000).unwrap();
+    assert_eq!(lp.staker().positions[0].reward_base, Uint128::new(4_000));
+}
@@ -0,0 +1,345 @@
+use cosmwasm_std::testing::{
+    mock_dependencies_with_balance, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+};
+use cosmwasm_std::{
+    coins, from_json, to_json_binary, Addr, BankMsg, ContractResult, CosmosMsg, Order, OwnedDeps,
+    Storage, SubMsg, SystemError, SystemResult, Uint128, WasmMsg, WasmQuery,
+};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw721::NumTokensResponse;
+use cw_storage_plus::Map;
+use incentive::constants;
+use incentive::contract::{execute, instantiate, migrate, query};
+use incentive::msg::{
+    CapacityResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, ReceiveMsg,
+    RewardDistribution, StakerResponse,
+};
+use incentive::ContractError;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+const FIXTURE: &str = include_str!("fixtures/v1_0_0_state.json");
+
+// the storage layout of the contract before 2.0.0
+const CONTRACT_INFO_KEY: &[u8] = b"contract_info";
+const CONFIG_KEY: &[u8] = b"config";
+const STAKERS: Map<&str, Value> = Map::new("stakers");
+
+const OWNER: &str = "owner";
+const HOLE: &str = "hole";
+const DENOM: &str = "ujuno";
+const STAKER_ONE: &str = "staker_one";
+const STAKER_TWO: &str = "staker_two";
+
+type Deps = OwnedDeps<MockStorage, MockApi, MockQuerier>;
+
+fn load_fixture() -> Deps {
+    let fixture: Value = serde_json::from_str(FIXTURE).unwrap();
+    let mut deps = mock_dependencies_with_balance(&coins(1_000_000_000, DENOM));
+    deps.storage.set(
+        CONTRACT_INFO_KEY,
+        &serde_json::to_vec(&fixture["contract_info"]).unwrap(),
+    );
+    deps.storage
+        .set(CONFIG_KEY, &serde_json::to_vec(&fixture["config"]).unwrap());
+    for (address, list) in fixture["stakers"].as_object().unwrap() {
+        STAKERS.save(&mut deps.storage, address, list).unwrap();
+    }
+    mock_hole_balance(&mut deps, 1_000_000_000);
+    deps
+}
+
+// the hole balance of the contract, with the stake and the reward
+fn mock_hole_balance(deps: &mut Deps, balance: u128) {
+    deps.querier.update_wasm(move |query| match query {
+        WasmQuery::Smart { contract_addr, .. } if contract_addr.as_str() == HOLE => {
+            SystemResult::Ok(ContractResult::Ok(
+                to_json_binary(&cw20::BalanceResponse {
+                    balance: Uint128::new(balance),
+                })
+                .unwrap(),
+            ))
+        }
+        _ => SystemResult::Err(SystemError::UnsupportedRequest {
+            kind: "wasm".to_string(),
+        }),
+    });
+}
+
+fn set_version(deps: &mut Deps, contract: &str, version: &str) {
+    cw2::set_contract_version(&mut deps.storage, contract, version).unwrap();
+}
+
+fn query_as<T: DeserializeOwned>(deps: &Deps, msg: QueryMsg) -> T {
+    from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap()
+}
+
+fn staker(deps: &Deps, address: &str) -> StakerResponse {
+    query_as(
+        deps,
+        QueryMsg::Staker {
+            pool_id: 0,
+            address: address.to_string(),
+        },
+    )
+}
+
+#[test]
+fn migrates_v1_stakers_into_positions() {
+    let mut deps = load_fixture();
+
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg::default()).unwrap();
+    let migrated = res
+        .attributes
+        .iter()
+        .find(|attr| attr.key == "migrated_positions")
+        .unwrap();
+    assert_eq!(migrated.value, "3");
+
+    let version = cw2::get_contract_version(&deps.storage).unwrap();
+    assert_eq!(version.contract, "incentive");
+    assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+
+    // the old config is kept and the new fields get the defaults
+    let config: ConfigResponse = query_as(&deps, QueryMsg::Config {});
+    assert_eq!(config.owner, Addr::unchecked(OWNER));
+    assert_eq!(config.stake_token_address, Addr::unchecked(HOLE));
+    assert_eq!(config.reward_token_denom, DENOM);
+    assert_eq!(config.reward_interval, constants::ONE_YEAR_SECONDS);
+    assert_eq!(
+        config.min_reward_interval,
+        constants::DEFAULT_MIN_REWARD_INTERVAL
+    );
+    assert_eq!(
+        config.max_reward_interval,
+        constants::DEFAULT_MAX_REWARD_INTERVAL
+    );
+    assert!(config.enabled);
+    assert!(!config.emergency);
+    assert_eq!(config.admin_timelock_seconds, 0);
+
+    // every staking record became a position of the default pool
+    let one = staker(&deps, STAKER_ONE);
+    assert_eq!(one.positions.len(), 2);
+    assert_eq!(one.total_staked, Uint128::new(1_500_000));
+    assert_eq!(one.positions[0].position_id, 0);
+    assert_eq!(one.positions[0].lock_type, constants::ONE_MONTH_SECONDS);
+    assert_eq!(one.positions[0].last_time, 1568797419);
+    assert_eq!(one.positions[1].position_id, 1);
+    assert_eq!(one.positions[1].lock_type, constants::ONE_YEAR_SECONDS);
+    // only the one month lock has passed
+    assert_eq!(one.pending_reward, Uint128::new(100_000));
+
+    let two = staker(&deps, STAKER_TWO);
+    assert_eq!(two.positions.len(), 1);
+    assert_eq!(two.positions[0].position_id, 2);
+    assert_eq!(two.positions[0].owner, Addr::unchecked(STAKER_TWO));
+    assert_eq!(two.pending_reward, Uint128::new(400_000));
+
+    let num_tokens: NumTokensResponse = query_as(&deps, QueryMsg::NumTokens {});
+    assert_eq!(num_tokens.count, 3);
+
+    let capacity: CapacityResponse = query_as(&deps, QueryMsg::Capacity { pool_id: 0 });
+    assert_eq!(capacity.total_staked, Uint128::new(3_500_000));
+    let tier_staked: Vec<Uint128> = capacity.tiers.iter().map(|tier| tier.staked).collect();
+    assert_eq!(
+        tier_staked,
+        vec![
+            Uint128::new(1_000_000),
+            Uint128::new(2_000_000),
+            Uint128::new(500_000),
+            Uint128::zero(),
+        ]
+    );
+
+    // the old stakers list is removed
+    let legacy = STAKERS
+        .range(&deps.storage, None, None, Order::Ascending)
+        .count();
+    assert_eq!(legacy, 0);
+}
+
+#[test]
+fn migrated_positions_can_be_claimed() {
+    let mut deps = load_fixture();
+    migrate(deps.as_mut(), mock_env(), MigrateMsg::default()).unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(STAKER_TWO, &[]),
+        ExecuteMsg::ClaimReward {
+            pool_id: 0,
+            distribution: RewardDistribution {
+                juno_reward: true,
+                charity: 0,
+                burn: 0,
+                artists: 0,
+            },
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HOLE.to_string(),
+                msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: STAKER_TWO.to_string(),
+                    amount: Uint128::new(400_000),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: STAKER_TWO.to_string(),
+                amount: coins(400_000, DENOM),
+            })),
+        ]
+    );
+    assert_eq!(staker(&deps, STAKER_TWO).pending_reward, Uint128::zero());
+}
+
+#[test]
+fn migrate_sets_new_config_fields() {
+    let mut deps = load_fixture();
+
+    migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            owner: Some("dao".to_string()),
+            min_reward_interval: Some(constants::ONE_MONTH_SECONDS),
+            max_reward_interval: Some(constants::ONE_YEAR_SECONDS),
+            min_stake_amount: Some(Uint128::new(100)),
+            max_positions_per_address: Some(10),
+            reward_vesting_seconds: Some(constants::ONE_MONTH_SECONDS),
+            admin_timelock_seconds: Some(86400),
+        },
+    )
+    .unwrap();
+
+    let config: ConfigResponse = query_as(&deps, QueryMsg::Config {});
+    assert_eq!(config.owner, Addr::unchecked("dao"));
+    assert_eq!(config.min_reward_interval, constants::ONE_MONTH_SECONDS);
+    assert_eq!(config.max_reward_interval, constants::ONE_YEAR_SECONDS);
+    assert_eq!(config.min_stake_amount, Uint128::new(100));
+    assert_eq!(config.max_positions_per_address, 10);
+    assert_eq!(config.reward_vesting_seconds, constants::ONE_MONTH_SECONDS);
+    assert_eq!(config.admin_timelock_seconds, 86400);
+}
+
+#[test]
+fn migrate_rejects_invalid_config_fields() {
+    let invalid = vec![
+        MigrateMsg {
+            owner: Some("Invalid".to_string()),
+            ..MigrateMsg::default()
+        },
+        MigrateMsg {
+            min_reward_interval: Some(0),
+            ..MigrateMsg::default()
+        },
+        MigrateMsg {
+            max_reward_interval: Some(constants::ONE_MONTH_SECONDS),
+            ..MigrateMsg::default()
+        },
+        MigrateMsg {
+            min_stake_amount: Some(Uint128::zero()),
+            ..MigrateMsg::default()
+        },
+        MigrateMsg {
+            admin_timelock_seconds: Some(constants::MAX_ADMIN_TIMELOCK_SECONDS + 1),
+            ..MigrateMsg::default()
+        },
+    ];
+    for msg in invalid {
+        let mut deps = load_fixture();
+        migrate(deps.as_mut(), mock_env(), msg).unwrap_err();
+    }
+}
+
+#[test]
+fn migrate_rejects_downgrade() {
+    let mut deps = load_fixture();
+    set_version(&mut deps, "incentive", "3.0.0");
+
+    let err = migrate(deps.as_mut(), mock_env(), MigrateMsg::default()).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::CannotDowngrade {
+            previous_version: "3.0.0".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    );
+}
+
+#[test]
+fn migrate_rejects_other_contract_and_invalid_version() {
+    let mut deps = load_fixture();
+    set_version(&mut deps, "crates.io:cw20-base", "1.0.0");
+    let err = migrate(deps.as_mut(), mock_env(), MigrateMsg::default()).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::CannotMigrate {
+            previous_contract: "crates.io:cw20-base".to_string(),
+        }
+    );
+
+    set_version(&mut deps, "incentive", "one");
+    let err = migrate(deps.as_mut(), mock_env(), MigrateMsg::default()).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidVersion {
+            version: "one".to_string(),
+        }
+    );
+}
+
+#[test]
+fn migrate_from_current_version_keeps_positions() {
+    let mut deps = mock_dependencies_with_balance(&[]);
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        InstantiateMsg {
+            stake_token_address: HOLE.to_string(),
+            reward_charity_address: "charity".to_string(),
+            reward_burn_address: "burn".to_string(),
+            reward_artists_address: "artists".to_string(),
+            reward_token_denom: DENOM.to_string(),
+            reward_interval: constants::ONE_YEAR_SECONDS,
+            owner: None,
+            min_reward_interval: None,
+            max_reward_interval: None,
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(HOLE, &[]),
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: STAKER_ONE.to_string(),
+            amount: Uint128::new(1_000_000),
+            msg: to_json_binary(&ReceiveMsg::Stake {
+                pool_id: 0,
+                lock_type: constants::LOCKED_ONE_MONTH,
+                recipient: None,
+            })
+            .unwrap(),
+        }),
+    )
+    .unwrap();
+
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg::default()).unwrap();
+    assert!(res
+        .attributes
+        .iter()
+        .all(|attr| attr.key != "migrated_positions"));
+
+    let one = staker(&deps, STAKER_ONE);
+    assert_eq!(one.positions.len(), 1);
+    assert_eq!(one.total_staked, Uint128::new(1_000_000));
+}
@@ -0,0 +1,335 @@
+use cosmwasm_std::testing::{
+    mock_dependencies_with_balance, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+};
+use cosmwasm_std::{
+    coins, from_json, to_json_binary, Addr, BankMsg, ContractResult, CosmosMsg, Decimal, Env,
+    OwnedDeps, SubMsg, SystemError, SystemResult, Timestamp, Uint128, WasmMsg, WasmQuery,
+};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, Denom};
+use incentive::constants;
+use incentive::contract::{calc_reward, default_tiers, execute, instantiate, update_reward};
+use incentive::msg::{ExecuteMsg, InstantiateMsg, Position, ReceiveMsg, RewardDistribution};
+use incentive::state::CONFIG;
+use incentive::ContractError;
+use proptest::prelude::*;
+
+const OWNER: &str = "owner";
+const USER: &str = "user";
+const HOLE: &str = "hole";
+const DENOM: &str = "ujuno";
+const POOL_ID: u64 = 0;
+
+// Timestamp keeps nanoseconds in a u64
+const MAX_SECONDS: u64 = u64::MAX / 1_000_000_000;
+
+type Deps = OwnedDeps<MockStorage, MockApi, MockQuerier>;
+
+fn env_at(seconds: u64) -> Env {
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(seconds);
+    env
+}
+
+fn setup(funded: u128, reward_interval: u64) -> Deps {
+    let mut deps = mock_dependencies_with_balance(&coins(funded, DENOM));
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        InstantiateMsg {
+            stake_token_address: HOLE.to_string(),
+            reward_charity_address: "charity".to_string(),
+            reward_burn_address: "burn".to_string(),
+            reward_artists_address: "artists".to_string(),
+            reward_token_denom: DENOM.to_string(),
+            reward_interval,
+            owner: None,
+            min_reward_interval: None,
+            max_reward_interval: None,
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        ExecuteMsg::UpdateStakeLimits {
+            min_stake_amount: Uint128::new(1),
+            max_positions_per_address: constants::DEFAULT_MAX_POSITIONS_PER_ADDRESS,
+        },
+    )
+    .unwrap();
+    deps
+}
+
+// the hole balance of the contract, with the stake and the reward
+fn mock_hole_balance(deps: &mut Deps, balance: u128) {
+    deps.querier.update_wasm(move |query| match query {
+        WasmQuery::Smart { contract_addr, .. } if contract_addr.as_str() == HOLE => {
+            SystemResult::Ok(ContractResult::Ok(
+                to_json_binary(&cw20::BalanceResponse {
+                    balance: Uint128::new(balance),
+                })
+                .unwrap(),
+            ))
+        }
+        _ => SystemResult::Err(SystemError::UnsupportedRequest {
+            kind: "wasm".to_string(),
+        }),
+    });
+}
+
+fn stake(deps: &mut Deps, env: Env, amount: u128, lock_type: u64) {
+    execute(
+        deps.as_mut(),
+        env,
+        mock_info(HOLE, &[]),
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: USER.to_string(),
+            amount: Uint128::new(amount),
+            msg: to_json_binary(&ReceiveMsg::Stake {
+                pool_id: POOL_ID,
+                lock_type,
+                recipient: None,
+            })
+            .unwrap(),
+        }),
+    )
+    .unwrap();
+}
+
+fn position(amount: u128, lock_seconds: u64, last_time: u64) -> Position {
+    Position {
+        position_id: 0,
+        pool_id: POOL_ID,
+        owner: Addr::unchecked(USER),
+        asset: Denom::Cw20(Addr::unchecked(HOLE)),
+        amount: Uint128::new(amount),
+        reward_base: Uint128::new(amount),
+        reward: Uint128::zero(),
+        accrued: Uint128::zero(),
+        last_time,
+        lock_type: lock_seconds,
+        reward_index: Decimal::zero(),
+        received: false,
+    }
+}
+
+fn amount() -> impl Strategy<Value = u128> {
+    prop_oneof![
+        1..=1_000_000_000_000u128,
+        any::<u128>().prop_map(|amount| amount.max(1))
+    ]
+}
+
+fn percent() -> impl Strategy<Value = u64> {
+    prop_oneof![8 => 0..=100u64, 1 => any::<u64>()]
+}
+
+fn distribution() -> impl Strategy<Value = RewardDistribution> {
+    (any::<bool>(), percent(), percent(), percent()).prop_map(
+        |(juno_reward, charity, burn, artists)| RewardDistribution {
+            juno_reward,
+            charity,
+            burn,
+            artists,
+        },
+    )
+}
+
+// the cw20 and native amounts sent by a claim
+fn payouts(msgs: &[SubMsg]) -> (Uint128, Uint128) {
+    let mut cw20 = Uint128::zero();
+    let mut native = Uint128::zero();
+    for msg in msgs {
+        match &msg.msg {
+            CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => match from_json(msg).unwrap() {
+                Cw20ExecuteMsg::Transfer { amount, .. } => cw20 += amount,
+                other => panic!("unexpected cw20 message {:?}", other),
+            },
+            CosmosMsg::Bank(BankMsg::Send { amount, .. }) => {
+                native += amount.iter().map(|coin| coin.amount).sum::<Uint128>()
+            }
+            other => panic!("unexpected message {:?}", other),
+        }
+    }
+    (cw20, native)
+}
+
+proptest! {
+    #[test]
+    fn calc_reward_never_panics(
+        amount in any::<u128>(),
+        lock_index in 0..4usize,
+        last_time in any::<u64>(),
+        now in any::<u64>(),
+        reward_interval in any::<u64>(),
+    ) {
+        let deps = setup(0, constants::ONE_YEAR_SECONDS);
+        let mut cfg = CONFIG.load(&deps.storage).unwrap();
+        cfg.reward_interval = reward_interval;
+
+        let tiers = default_tiers();
+        let staker = position(amount, tiers[lock_index].lock_seconds, last_time);
+        let _ = calc_reward(&cfg, &tiers, &staker, staker.reward_base, now);
+    }
+
+    #[test]
+    fn reward_is_monotonic_in_time(
+        amount in amount(),
+        lock_index in 0..4usize,
+        last_time in 0..=MAX_SECONDS,
+        elapsed in 0..=constants::TWO_YEAR_SECONDS * 4,
+        later in 0..=constants::TWO_YEAR_SECONDS * 4,
+    ) {
+        let deps = setup(0, constants::ONE_YEAR_SECONDS);
+        let cfg = CONFIG.load(&deps.storage).unwrap();
+        let tiers = default_tiers();
+        let staker = position(amount, tiers[lock_index].lock_seconds, last_time);
+
+        let now = last_time.saturating_add(elapsed);
+        let reward = calc_reward(&cfg, &tiers, &staker, staker.reward_base, now).unwrap();
+        let later_reward =
+            calc_reward(&cfg, &tiers, &staker, staker.reward_base, now.saturating_add(later)).unwrap();
+        prop_assert!(reward <= later_reward);
+    }
+
+    #[test]
+    fn reward_is_monotonic_in_lock_length(
+        amount in amount(),
+        shorter in 0..4usize,
+        longer in 0..4usize,
+        elapsed in 0..=constants::TWO_YEAR_SECONDS * 4,
+    ) {
+        prop_assume!(shorter <= longer);
+        let deps = setup(0, constants::ONE_YEAR_SECONDS);
+        let cfg = CONFIG.load(&deps.storage).unwrap();
+        let tiers = default_tiers();
+
+        // both locks have passed
+        let now = tiers[longer].lock_seconds + elapsed;
+        let short = position(amount, tiers[shorter].lock_seconds, 0);
+        let long = position(amount, tiers[longer].lock_seconds, 0);
+        let short_reward = calc_reward(&cfg, &tiers, &short, short.reward_base, now).unwrap();
+        let long_reward = calc_reward(&cfg, &tiers, &long, long.reward_base, now).unwrap();
+        prop_assert!(short_reward <= long_reward);
+    }
+
+    #[test]
+    fn update_reward_handles_any_block_time(
+        amount in amount(),
+        lock_type in 0..4u64,
+        staked_at in 0..=MAX_SECONDS,
+        now in 0..=MAX_SECONDS,
+    ) {
+        let mut deps = setup(0, constants::ONE_YEAR_SECONDS);
+        stake(&mut deps, env_at(staked_at), amount, lock_type);
+
+        // the block time may be before the record, which must not underflow
+        let reward = update_reward(
+            &mut deps.storage,
+            env_at(now),
+            POOL_ID,
+            Addr::unchecked(USER),
+        )
+        .unwrap();
+        if now < staked_at {
+            prop_assert_eq!(reward, Uint128::zero());
+        }
+    }
+
+    #[test]
+    fn claim_never_pays_more_than_funded_or_earned(
+        amount in amount(),
+        lock_type in 0..4u64,
+        elapsed in 0..=constants::TWO_YEAR_SECONDS * 2,
+        funded in prop_oneof![0..=1_000_000_000_000u128, any::<u128>()],
+        hole_funded in prop_oneof![0..=1_000_000_000_000u128, any::<u128>()],
+        distribution in distribution(),
+    ) {
+        let mut deps = setup(funded, constants::ONE_YEAR_SECONDS);
+        let staked_at = mock_env().block.time.seconds();
+        stake(&mut deps, env_at(staked_at), amount, lock_type);
+        // the hole reward is funded on top of the stake, which is never paid as the reward
+        let hole_balance = amount.saturating_add(hole_funded);
+        mock_hole_balance(&mut deps, hole_balance);
+
+        let distributed = distribution.charity as u128
+            + distribution.burn as u128
+            + distribution.artists as u128;
+        let juno_reward = distribution.juno_reward;
+        let res = execute(
+            deps.as_mut(),
+            env_at(staked_at + elapsed),
+            mock_info(USER, &[]),
+            ExecuteMsg::ClaimReward {
+                pool_id: POOL_ID,
+                distribution,
+            },
+        );
+
+        match res {
+            Ok(res) => {
+                prop_assert!(distributed <= 100);
+                let (cw20, native) = payouts(&res.messages);
+                // the juno reward is the same amount as the hole reward
+                prop_assert!(native <= cw20);
+                prop_assert!(native <= Uint128::new(funded));
+                prop_assert!(cw20 <= Uint128::new(hole_balance - amount));
+                if !juno_reward {
+                    prop_assert_eq!(native, Uint128::zero());
+                }
+                let reward_amount = res
+                    .attributes
+                    .iter()
+                    .find(|attr| attr.key == "reward_amount")
+                    .unwrap();
+                prop_assert_eq!(reward_amount.value.clone(), cw20.to_string());
+            }
+            Err(ContractError::InvalidInput {}) => prop_assert!(distributed > 100),
+            Err(ContractError::NotEnoughReward {}) => prop_assert!(distributed <= 100),
+            Err(err) => prop_assert!(false, "unexpected error {}", err),
+        }
+    }
+
+    #[test]
+    fn claim_pays_reward_once(
+        amount in 1..=1_000_000_000_000u128,
+        lock_type in 0..4u64,
+        elapsed in 0..=constants::TWO_YEAR_SECONDS * 2,
+    ) {
+        let mut deps = setup(u128::MAX, constants::ONE_YEAR_SECONDS);
+        let staked_at = mock_env().block.time.seconds();
+        stake(&mut deps, env_at(staked_at), amount, lock_type);
+        mock_hole_balance(&mut deps, u128::MAX);
+
+        let claim = |deps: &mut Deps| {
+            let res = execute(
+                deps.as_mut(),
+                env_at(staked_at + elapsed),
+                mock_info(USER, &[]),
+                ExecuteMsg::ClaimReward {
+                    pool_id: POOL_ID,
+                    distribution: RewardDistribution {
+                        juno_reward: true,
+                        charity: 0,
+                        burn: 0,
+                        artists: 0,
+                    },
+                },
+            )
+            .unwrap();
+            payouts(&res.messages)
+        };
+
+        let (cw20, native) = claim(&mut deps);
+        let tiers = default_tiers();
+        let apy = tiers[lock_type as usize].apy as u128;
+        let max_reward = Uint128::new(amount).multiply_ratio(apy, 100u128);
+        prop_assert!(cw20 <= max_reward);
+        prop_assert_eq!(native, cw20);
+
+        // nothing is left to claim in the same block
+        prop_assert_eq!(claim(&mut deps), (Uint128::zero(), Uint128::zero()));
+    }
+}